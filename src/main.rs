@@ -1,7 +1,34 @@
+use std::{env, path::Path};
+
+mod util;
 mod y2015;
 mod y2024;
 
+// progress for the long searches is kept here between runs
+const CHECKPOINT: &str = "checkpoint.txt";
+
 fn main() {
-    let res = y2024::d22::sell_for_bananas_better("input.txt");
-    println!("res: {res}");
+    let args: Vec<String> = env::args().skip(1).collect();
+    let resume = args.iter().any(|arg| arg == "--resume");
+    let checkpoint = Path::new(CHECKPOINT);
+    match args.first().map(String::as_str) {
+        Some("2015/4") => {
+            let prefix = args.get(1).expect("usage: 2015/4 <prefix> [--resume]");
+            let res = y2015::d4::find_lowest_hash_resumable(prefix, 6, checkpoint, resume).unwrap();
+            println!("res: {res}");
+        }
+        Some("2024/14") => {
+            let res =
+                y2024::d14::part2_estimate_resumable("input.txt", 101, 103, checkpoint, resume)
+                    .unwrap();
+            match res {
+                Some(tick) => println!("res: {tick}"),
+                None => println!("no tick looks like a tree"),
+            }
+        }
+        _ => {
+            let res = y2024::d22::sell_for_bananas_better("input.txt");
+            println!("res: {res}");
+        }
+    }
 }
@@ -0,0 +1,206 @@
+#![allow(dead_code)]
+
+use std::{
+    cmp::Ordering,
+    fmt,
+    iter::Sum,
+    ops::{Add, AddAssign, Mul},
+};
+
+/// Anything a counting solver can accumulate into. Fixed width types are
+/// fastest; `BigUint` never overflows.
+pub trait Count: Clone + Default + PartialEq + AddAssign + Sum + fmt::Display {
+    fn one() -> Self;
+
+    fn is_zero(&self) -> bool {
+        *self == Self::default()
+    }
+}
+impl Count for u32 {
+    fn one() -> Self {
+        1
+    }
+}
+impl Count for u64 {
+    fn one() -> Self {
+        1
+    }
+}
+impl Count for usize {
+    fn one() -> Self {
+        1
+    }
+}
+impl Count for u128 {
+    fn one() -> Self {
+        1
+    }
+}
+impl Count for BigUint {
+    fn one() -> Self {
+        BigUint::from(1u64)
+    }
+}
+
+/// Arbitrary precision unsigned integer, stored as little endian base 2^32 limbs.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct BigUint {
+    // no trailing zero limbs, so zero is empty
+    limbs: Vec<u32>,
+}
+impl BigUint {
+    fn trim(&mut self) {
+        while self.limbs.last() == Some(&0) {
+            self.limbs.pop();
+        }
+    }
+
+    // divides in place, returning the remainder
+    fn div_rem_small(&mut self, divisor: u32) -> u32 {
+        let mut rem = 0u64;
+        for limb in self.limbs.iter_mut().rev() {
+            let cur = (rem << 32) | *limb as u64;
+            *limb = (cur / divisor as u64) as u32;
+            rem = cur % divisor as u64;
+        }
+        self.trim();
+        rem as u32
+    }
+}
+impl From<u64> for BigUint {
+    fn from(v: u64) -> Self {
+        let mut n = BigUint {
+            limbs: vec![v as u32, (v >> 32) as u32],
+        };
+        n.trim();
+        n
+    }
+}
+impl From<u128> for BigUint {
+    fn from(v: u128) -> Self {
+        let mut n = BigUint {
+            limbs: (0..4).map(|i| (v >> (32 * i)) as u32).collect(),
+        };
+        n.trim();
+        n
+    }
+}
+impl AddAssign<&BigUint> for BigUint {
+    fn add_assign(&mut self, rhs: &BigUint) {
+        if self.limbs.len() < rhs.limbs.len() {
+            self.limbs.resize(rhs.limbs.len(), 0);
+        }
+        let mut carry = 0u64;
+        for (i, limb) in self.limbs.iter_mut().enumerate() {
+            let sum = *limb as u64 + *rhs.limbs.get(i).unwrap_or(&0) as u64 + carry;
+            *limb = sum as u32;
+            carry = sum >> 32;
+            if carry == 0 && i >= rhs.limbs.len() {
+                break;
+            }
+        }
+        if carry > 0 {
+            self.limbs.push(carry as u32);
+        }
+    }
+}
+impl AddAssign for BigUint {
+    fn add_assign(&mut self, rhs: BigUint) {
+        *self += &rhs;
+    }
+}
+impl Add for BigUint {
+    type Output = BigUint;
+
+    fn add(mut self, rhs: BigUint) -> BigUint {
+        self += &rhs;
+        self
+    }
+}
+impl Mul for &BigUint {
+    type Output = BigUint;
+
+    fn mul(self, rhs: &BigUint) -> BigUint {
+        let mut limbs = vec![0u32; self.limbs.len() + rhs.limbs.len()];
+        for (i, a) in self.limbs.iter().enumerate() {
+            let mut carry = 0u64;
+            for (j, b) in rhs.limbs.iter().enumerate() {
+                let cur = limbs[i + j] as u64 + (*a as u64 * *b as u64) + carry;
+                limbs[i + j] = cur as u32;
+                carry = cur >> 32;
+            }
+            limbs[i + rhs.limbs.len()] = carry as u32;
+        }
+        let mut n = BigUint { limbs };
+        n.trim();
+        n
+    }
+}
+impl Sum for BigUint {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(BigUint::default(), |acc, x| acc + x)
+    }
+}
+impl Ord for BigUint {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.limbs
+            .len()
+            .cmp(&other.limbs.len())
+            .then_with(|| self.limbs.iter().rev().cmp(other.limbs.iter().rev()))
+    }
+}
+impl PartialOrd for BigUint {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl fmt::Display for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.limbs.is_empty() {
+            return f.pad("0");
+        }
+        // peel off 9 decimal digits at a time
+        let mut n = self.clone();
+        let mut chunks = Vec::new();
+        while !n.limbs.is_empty() {
+            chunks.push(n.div_rem_small(1_000_000_000));
+        }
+        let mut s = chunks.pop().unwrap().to_string();
+        for chunk in chunks.iter().rev() {
+            s.push_str(&format!("{chunk:09}"));
+        }
+        f.pad(&s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BigUint;
+
+    #[test]
+    fn add_carries() {
+        let mut n = BigUint::from(u64::MAX);
+        n += BigUint::from(1u64);
+        assert_eq!(n, BigUint::from(1u128 << 64));
+        assert_eq!(n.to_string(), "18446744073709551616");
+        assert_eq!(BigUint::default().to_string(), "0");
+    }
+
+    #[test]
+    fn mul_and_display() {
+        let a = BigUint::from(u128::MAX);
+        let b = &a * &a;
+        assert_eq!(
+            b.to_string(),
+            "115792089237316195423570985008687907852589419931798687112530834793049593217025"
+        );
+        assert!(b > a);
+        assert_eq!(&a * &BigUint::default(), BigUint::default());
+    }
+
+    #[test]
+    fn sums() {
+        let total: BigUint = (0..1000).map(|_| BigUint::from(u64::MAX)).sum();
+        assert_eq!(total.to_string(), "18446744073709551615000");
+    }
+}
@@ -0,0 +1,207 @@
+#![allow(dead_code)]
+
+use std::ops::Range;
+
+pub const NORTH: u8 = 0b0001;
+pub const EAST: u8 = 0b0010;
+pub const SOUTH: u8 = 0b0100;
+pub const WEST: u8 = 0b1000;
+
+/// Fixed size grid of bits of any dimensions. Each row starts on a fresh
+/// `u64` so row operations work a word at a time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BitGrid {
+    words: Vec<u64>,
+    width: usize,
+    height: usize,
+    row_words: usize,
+}
+impl BitGrid {
+    pub fn new(width: usize, height: usize) -> Self {
+        let row_words = width.div_ceil(64);
+        BitGrid {
+            words: vec![0; row_words * height],
+            width,
+            height,
+            row_words,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    fn index(&self, x: usize, y: usize) -> (usize, u64) {
+        assert!(
+            x < self.width && y < self.height,
+            "({x}, {y}) out of bounds"
+        );
+        ((y * self.row_words) + (x / 64), 1 << (x % 64))
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> bool {
+        let (word, bit) = self.index(x, y);
+        self.words[word] & bit != 0
+    }
+
+    pub fn set(&mut self, x: usize, y: usize) {
+        let (word, bit) = self.index(x, y);
+        self.words[word] |= bit;
+    }
+
+    pub fn clear(&mut self, x: usize, y: usize) {
+        let (word, bit) = self.index(x, y);
+        self.words[word] &= !bit;
+    }
+
+    /// Sets the bit and reports whether it was already set.
+    pub fn test_and_set(&mut self, x: usize, y: usize) -> bool {
+        let (word, bit) = self.index(x, y);
+        let was_set = self.words[word] & bit != 0;
+        self.words[word] |= bit;
+        was_set
+    }
+
+    fn row(&self, y: usize) -> &[u64] {
+        &self.words[y * self.row_words..(y + 1) * self.row_words]
+    }
+
+    fn row_mut(&mut self, y: usize) -> &mut [u64] {
+        &mut self.words[y * self.row_words..(y + 1) * self.row_words]
+    }
+
+    pub fn set_row_range(&mut self, y: usize, xs: Range<usize>) {
+        assert!(xs.end <= self.width);
+        for x in xs {
+            self.row_mut(y)[x / 64] |= 1 << (x % 64);
+        }
+    }
+
+    pub fn clear_row(&mut self, y: usize) {
+        self.row_mut(y).fill(0);
+    }
+
+    pub fn clear_all(&mut self) {
+        self.words.fill(0);
+    }
+
+    pub fn union_with(&mut self, other: &BitGrid) {
+        assert_eq!((self.width, self.height), (other.width, other.height));
+        self.words
+            .iter_mut()
+            .zip(other.words.iter())
+            .for_each(|(a, b)| *a |= b);
+    }
+
+    pub fn intersect_with(&mut self, other: &BitGrid) {
+        assert_eq!((self.width, self.height), (other.width, other.height));
+        self.words
+            .iter_mut()
+            .zip(other.words.iter())
+            .for_each(|(a, b)| *a &= b);
+    }
+
+    pub fn row_count_ones(&self, y: usize) -> usize {
+        self.row(y).iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn iter_ones(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        (0..self.height).flat_map(move |y| {
+            self.row(y)
+                .iter()
+                .enumerate()
+                .flat_map(move |(i, word)| {
+                    let mut word = *word;
+                    std::iter::from_fn(move || {
+                        if word == 0 {
+                            return None;
+                        }
+                        let bit = word.trailing_zeros() as usize;
+                        word &= word - 1;
+                        Some((i * 64) + bit)
+                    })
+                })
+                .map(move |x| (x, y))
+        })
+    }
+
+    /// Which of the four orthogonal neighbours are set, as `NORTH | EAST | SOUTH | WEST`
+    /// bits. Neighbours off the grid count as unset.
+    pub fn neighbour_mask(&self, x: usize, y: usize) -> u8 {
+        let mut mask = 0;
+        if y > 0 && self.get(x, y - 1) {
+            mask |= NORTH;
+        }
+        if x + 1 < self.width && self.get(x + 1, y) {
+            mask |= EAST;
+        }
+        if y + 1 < self.height && self.get(x, y + 1) {
+            mask |= SOUTH;
+        }
+        if x > 0 && self.get(x - 1, y) {
+            mask |= WEST;
+        }
+        mask
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BitGrid, EAST, NORTH, SOUTH, WEST};
+
+    #[test]
+    fn set_get_clear_wide() {
+        let mut grid = BitGrid::new(300, 3);
+        grid.set(0, 0);
+        grid.set(63, 1);
+        grid.set(64, 1);
+        grid.set(299, 2);
+        assert!(grid.get(63, 1) && grid.get(64, 1) && grid.get(299, 2));
+        assert!(!grid.get(65, 1) && !grid.get(299, 1));
+        assert!(grid.test_and_set(0, 0));
+        assert!(!grid.test_and_set(1, 0));
+        grid.clear(1, 0);
+        assert_eq!(grid.count_ones(), 4);
+        assert_eq!(grid.row_count_ones(1), 2);
+        assert_eq!(
+            grid.iter_ones().collect::<Vec<_>>(),
+            [(0, 0), (63, 1), (64, 1), (299, 2)]
+        );
+    }
+
+    #[test]
+    fn row_operations() {
+        let mut a = BitGrid::new(130, 2);
+        a.set_row_range(0, 60..130);
+        assert_eq!(a.row_count_ones(0), 70);
+        let mut b = BitGrid::new(130, 2);
+        b.set_row_range(0, 0..65);
+        b.set(5, 1);
+        let mut union = a.clone();
+        union.union_with(&b);
+        assert_eq!(union.count_ones(), 131);
+        a.intersect_with(&b);
+        assert_eq!(a.count_ones(), 5);
+        union.clear_row(0);
+        assert_eq!(union.count_ones(), 1);
+    }
+
+    #[test]
+    fn neighbours() {
+        let mut grid = BitGrid::new(3, 3);
+        grid.set(1, 0);
+        grid.set(0, 1);
+        grid.set(2, 2);
+        assert_eq!(grid.neighbour_mask(1, 1), NORTH | WEST);
+        assert_eq!(grid.neighbour_mask(2, 1), SOUTH);
+        assert_eq!(grid.neighbour_mask(0, 0), EAST | SOUTH);
+    }
+}
@@ -0,0 +1,146 @@
+#![allow(dead_code)]
+
+use std::{
+    collections::BTreeMap,
+    fmt::Display,
+    fs, io,
+    path::PathBuf,
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+/// Named values describing how far a search has got.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Progress(BTreeMap<String, String>);
+impl Progress {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set(mut self, key: &str, value: impl Display) -> Self {
+        self.0.insert(key.to_string(), value.to_string());
+        self
+    }
+
+    pub fn get<T: FromStr>(&self, key: &str) -> Option<T> {
+        self.0.get(key)?.parse().ok()
+    }
+}
+
+/// Saves a search's progress to a `key=value` file so a later run can pick up
+/// where it stopped. The file also names the search, so progress from a
+/// different prefix or target is never resumed by mistake.
+pub struct Checkpoint {
+    path: PathBuf,
+    search: String,
+    every: Duration,
+    last_save: Instant,
+}
+impl Checkpoint {
+    pub fn new(path: impl Into<PathBuf>, search: impl Into<String>) -> Self {
+        Checkpoint {
+            path: path.into(),
+            search: search.into(),
+            every: Duration::from_secs(5),
+            last_save: Instant::now(),
+        }
+    }
+
+    pub fn every(self, every: Duration) -> Self {
+        Checkpoint { every, ..self }
+    }
+
+    /// `None` if nothing has been saved yet.
+    pub fn load(&self) -> io::Result<Option<Progress>> {
+        let contents = match fs::read_to_string(&self.path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        let mut progress = Progress::new();
+        for line in contents.lines() {
+            let (key, value) = line.split_once('=').ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidData, format!("bad line {line:?}"))
+            })?;
+            progress.0.insert(key.to_string(), value.to_string());
+        }
+        match progress.0.remove("search") {
+            Some(search) if search == self.search => Ok(Some(progress)),
+            other => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "{} holds progress for {:?}, not {:?}",
+                    self.path.display(),
+                    other.unwrap_or_default(),
+                    self.search
+                ),
+            )),
+        }
+    }
+
+    pub fn save(&mut self, progress: &Progress) -> io::Result<()> {
+        let mut contents = format!("search={}\n", self.search);
+        for (key, value) in &progress.0 {
+            contents.push_str(&format!("{key}={value}\n"));
+        }
+        // write then rename, so being killed mid-write leaves the old checkpoint intact
+        let tmp = self.path.with_extension("tmp");
+        fs::write(&tmp, contents)?;
+        fs::rename(&tmp, &self.path)?;
+        self.last_save = Instant::now();
+        Ok(())
+    }
+
+    /// Saves only if the interval has passed since the last save; `progress`
+    /// isn't called otherwise.
+    pub fn save_every(&mut self, progress: impl FnOnce() -> Progress) -> io::Result<()> {
+        if self.last_save.elapsed() >= self.every {
+            self.save(&progress())?;
+        }
+        Ok(())
+    }
+
+    /// The search is done, so there's nothing left to resume.
+    pub fn finish(self) -> io::Result<()> {
+        match fs::remove_file(&self.path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, time::Duration};
+
+    use super::{Checkpoint, Progress};
+
+    #[test]
+    fn save_and_load() {
+        let path = env::temp_dir().join("aoc_checkpoint_roundtrip.txt");
+        let mut checkpoint = Checkpoint::new(&path, "abc/5");
+        checkpoint.save(&Progress::new().set("next", 42)).unwrap();
+        let progress = checkpoint.load().unwrap().unwrap();
+        assert_eq!(progress.get::<usize>("next"), Some(42));
+        assert_eq!(progress.get::<usize>("missing"), None);
+
+        let other = Checkpoint::new(&path, "abc/6");
+        assert!(other.load().unwrap_err().to_string().contains("\"abc/5\""));
+
+        checkpoint.finish().unwrap();
+        assert_eq!(Checkpoint::new(&path, "abc/5").load().unwrap(), None);
+    }
+
+    #[test]
+    fn saves_on_interval() {
+        let path = env::temp_dir().join("aoc_checkpoint_interval.txt");
+        let mut checkpoint = Checkpoint::new(&path, "tick").every(Duration::from_secs(3600));
+        checkpoint.save_every(|| panic!("saved too early")).unwrap();
+        let mut checkpoint = checkpoint.every(Duration::ZERO);
+        checkpoint
+            .save_every(|| Progress::new().set("tick", 7))
+            .unwrap();
+        assert_eq!(checkpoint.load().unwrap().unwrap().get("tick"), Some(7));
+        checkpoint.finish().unwrap();
+    }
+}
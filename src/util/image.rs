@@ -0,0 +1,494 @@
+#![allow(dead_code)]
+
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+    time::Duration,
+};
+
+pub type Rgb = [u8; 3];
+
+/// Grid of palette indices, one per cell. Colours and scale are only applied on export.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    pixels: Vec<u8>,
+    width: usize,
+    height: usize,
+}
+impl Image {
+    pub fn new(width: usize, height: usize) -> Self {
+        Image {
+            pixels: vec![0; width * height],
+            width,
+            height,
+        }
+    }
+
+    pub fn from_fn(width: usize, height: usize, f: impl Fn(usize, usize) -> u8) -> Self {
+        let pixels = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| f(x, y))
+            .collect();
+        Image {
+            pixels,
+            width,
+            height,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> u8 {
+        self.pixels[(y * self.width) + x]
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, color: u8) {
+        self.pixels[(y * self.width) + x] = color;
+    }
+
+    fn scaled(&self, scale: usize) -> Image {
+        if scale == 1 {
+            return self.clone();
+        }
+        Image::from_fn(self.width * scale, self.height * scale, |x, y| {
+            self.get(x / scale, y / scale)
+        })
+    }
+}
+
+/// Palette and scale shared by every export format.
+pub struct Export {
+    palette: Vec<Rgb>,
+    scale: usize,
+}
+impl Export {
+    pub fn new(palette: &[Rgb]) -> Self {
+        assert!(!palette.is_empty() && palette.len() <= 256);
+        Export {
+            palette: palette.to_vec(),
+            scale: 1,
+        }
+    }
+
+    pub fn scale(self, scale: usize) -> Self {
+        assert!(scale > 0);
+        Export { scale, ..self }
+    }
+
+    /// Writes a PNG or PPM depending on the file extension.
+    pub fn save(&self, image: &Image, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        let mut out = BufWriter::new(File::create(path)?);
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("ppm") => self.write_ppm(image, &mut out)?,
+            Some("png") => self.write_png(image, &mut out)?,
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "expected a .png or .ppm path",
+                ))
+            }
+        }
+        out.flush()
+    }
+
+    pub fn save_gif(
+        &self,
+        frames: impl IntoIterator<Item = Image>,
+        delay: Duration,
+        path: impl AsRef<Path>,
+    ) -> io::Result<()> {
+        let mut out = BufWriter::new(File::create(path)?);
+        self.write_gif(frames, delay, &mut out)?;
+        out.flush()
+    }
+
+    // palette indices past the end would otherwise panic or write a broken file
+    fn check_palette(&self, image: &Image) -> io::Result<()> {
+        match image
+            .pixels
+            .iter()
+            .position(|p| *p as usize >= self.palette.len())
+        {
+            Some(i) => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "pixel ({}, {}) is colour {} but the palette has {}",
+                    i % image.width,
+                    i / image.width,
+                    image.pixels[i],
+                    self.palette.len()
+                ),
+            )),
+            None => Ok(()),
+        }
+    }
+
+    pub fn write_ppm(&self, image: &Image, out: &mut impl Write) -> io::Result<()> {
+        self.check_palette(image)?;
+        let image = image.scaled(self.scale);
+        write!(out, "P6\n{} {}\n255\n", image.width, image.height)?;
+        let bytes: Vec<u8> = image
+            .pixels
+            .iter()
+            .flat_map(|p| self.palette[*p as usize])
+            .collect();
+        out.write_all(&bytes)
+    }
+
+    // indexed colour with the deflate stream written as stored (uncompressed) blocks
+    pub fn write_png(&self, image: &Image, out: &mut impl Write) -> io::Result<()> {
+        self.check_palette(image)?;
+        let image = image.scaled(self.scale);
+        out.write_all(b"\x89PNG\r\n\x1a\n")?;
+
+        let mut header = Vec::new();
+        header.extend((image.width as u32).to_be_bytes());
+        header.extend((image.height as u32).to_be_bytes());
+        // bit depth 8, palette colour, default compression/filter/interlace
+        header.extend([8, 3, 0, 0, 0]);
+        write_png_chunk(out, b"IHDR", &header)?;
+        write_png_chunk(out, b"PLTE", &self.palette.concat())?;
+
+        let mut raw = Vec::with_capacity((image.width + 1) * image.height);
+        for row in image.pixels.chunks(image.width.max(1)) {
+            raw.push(0); // no filter
+            raw.extend(row);
+        }
+        write_png_chunk(out, b"IDAT", &zlib_stored(&raw))?;
+        write_png_chunk(out, b"IEND", &[])
+    }
+
+    /// Writes every frame into one looping GIF.
+    pub fn write_gif(
+        &self,
+        frames: impl IntoIterator<Item = Image>,
+        delay: Duration,
+        out: &mut impl Write,
+    ) -> io::Result<()> {
+        let mut frames = frames.into_iter().map(|f| f.scaled(self.scale)).peekable();
+        let (width, height) = match frames.peek() {
+            Some(first) => (first.width as u16, first.height as u16),
+            None => (0, 0),
+        };
+        // the colour table must have a power of two entries, at least 2
+        let mut table_bits = 1;
+        while (1 << table_bits) < self.palette.len() {
+            table_bits += 1;
+        }
+
+        out.write_all(b"GIF89a")?;
+        out.write_all(&width.to_le_bytes())?;
+        out.write_all(&height.to_le_bytes())?;
+        out.write_all(&[0xF0 | (table_bits - 1), 0, 0])?;
+        for i in 0..(1 << table_bits) {
+            out.write_all(self.palette.get(i).unwrap_or(&[0, 0, 0]))?;
+        }
+        // loop forever
+        out.write_all(b"\x21\xFF\x0BNETSCAPE2.0\x03\x01\x00\x00\x00")?;
+
+        let delay = (delay.as_millis() / 10) as u16;
+        let min_code_size = table_bits.max(2);
+        for frame in frames {
+            self.check_palette(&frame)?;
+            assert_eq!((frame.width as u16, frame.height as u16), (width, height));
+            out.write_all(&[0x21, 0xF9, 0x04, 0x00])?;
+            out.write_all(&delay.to_le_bytes())?;
+            out.write_all(&[0x00, 0x00])?;
+
+            out.write_all(&[0x2C, 0, 0, 0, 0])?;
+            out.write_all(&width.to_le_bytes())?;
+            out.write_all(&height.to_le_bytes())?;
+            out.write_all(&[0x00, min_code_size])?;
+            for block in lzw_encode(&frame.pixels, min_code_size).chunks(255) {
+                out.write_all(&[block.len() as u8])?;
+                out.write_all(block)?;
+            }
+            out.write_all(&[0x00])?;
+        }
+        out.write_all(&[0x3B])
+    }
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for b in bytes {
+        crc ^= *b as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB88320 & mask);
+        }
+    }
+    !crc
+}
+
+fn adler32(bytes: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for chunk in bytes.chunks(5552) {
+        for x in chunk {
+            a += *x as u32;
+            b += a;
+        }
+        a %= 65521;
+        b %= 65521;
+    }
+    (b << 16) | a
+}
+
+fn write_png_chunk(out: &mut impl Write, kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    out.write_all(&(data.len() as u32).to_be_bytes())?;
+    let mut crc_input = kind.to_vec();
+    crc_input.extend(data);
+    out.write_all(&crc_input)?;
+    out.write_all(&crc32(&crc_input).to_be_bytes())
+}
+
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut out = vec![0x78, 0x01];
+    let mut blocks = data.chunks(u16::MAX as usize).peekable();
+    if blocks.peek().is_none() {
+        out.extend([1, 0, 0, 0xFF, 0xFF]);
+    }
+    while let Some(block) = blocks.next() {
+        out.push(if blocks.peek().is_none() { 1 } else { 0 });
+        let len = block.len() as u16;
+        out.extend(len.to_le_bytes());
+        out.extend((!len).to_le_bytes());
+        out.extend(block);
+    }
+    out.extend(adler32(data).to_be_bytes());
+    out
+}
+
+struct BitWriter {
+    bytes: Vec<u8>,
+    acc: u32,
+    bits: u8,
+}
+impl BitWriter {
+    fn push(&mut self, code: u16, size: u8) {
+        self.acc |= (code as u32) << self.bits;
+        self.bits += size;
+        while self.bits >= 8 {
+            self.bytes.push(self.acc as u8);
+            self.acc >>= 8;
+            self.bits -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.bits > 0 {
+            self.bytes.push(self.acc as u8);
+        }
+        self.bytes
+    }
+}
+
+// GIF flavour of LZW: variable width codes packed LSB first, 12 bit maximum
+fn lzw_encode(pixels: &[u8], min_code_size: u8) -> Vec<u8> {
+    let clear = 1u16 << min_code_size;
+    let end = clear + 1;
+    let mut writer = BitWriter {
+        bytes: Vec::new(),
+        acc: 0,
+        bits: 0,
+    };
+    let mut dict: HashMap<(u16, u8), u16> = HashMap::new();
+    let mut next_code = end + 1;
+    let mut code_size = min_code_size + 1;
+
+    writer.push(clear, code_size);
+    let Some((first, rest)) = pixels.split_first() else {
+        writer.push(end, code_size);
+        return writer.finish();
+    };
+    let mut prefix = *first as u16;
+    for p in rest {
+        if let Some(code) = dict.get(&(prefix, *p)) {
+            prefix = *code;
+            continue;
+        }
+        writer.push(prefix, code_size);
+        if next_code < 4096 {
+            dict.insert((prefix, *p), next_code);
+            next_code += 1;
+            // the decoder adds entries one code behind us
+            if next_code > (1 << code_size) && code_size < 12 {
+                code_size += 1;
+            }
+        } else {
+            writer.push(clear, code_size);
+            dict.clear();
+            next_code = end + 1;
+            code_size = min_code_size + 1;
+        }
+        prefix = *p as u16;
+    }
+    writer.push(prefix, code_size);
+    writer.push(end, code_size);
+    writer.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{io, time::Duration};
+
+    use super::{adler32, crc32, lzw_encode, Export, Image};
+
+    // reference decoder, only used to check the encoder round trips
+    fn lzw_decode(bytes: &[u8], min_code_size: u8) -> Vec<u8> {
+        let clear = 1usize << min_code_size;
+        let end = clear + 1;
+        let mut table: Vec<Vec<u8>> = Vec::new();
+        let reset = |table: &mut Vec<Vec<u8>>| {
+            *table = (0..clear).map(|i| vec![i as u8]).collect();
+            table.push(Vec::new());
+            table.push(Vec::new());
+        };
+        reset(&mut table);
+        let mut code_size = min_code_size + 1;
+        let (mut acc, mut bits, mut idx) = (0u32, 0u8, 0);
+        let mut prev: Option<Vec<u8>> = None;
+        let mut out = Vec::new();
+        loop {
+            while bits < code_size {
+                acc |= (bytes[idx] as u32) << bits;
+                idx += 1;
+                bits += 8;
+            }
+            let code = (acc & ((1 << code_size) - 1)) as usize;
+            acc >>= code_size;
+            bits -= code_size;
+            if code == clear {
+                reset(&mut table);
+                code_size = min_code_size + 1;
+                prev = None;
+                continue;
+            }
+            if code == end {
+                return out;
+            }
+            let entry = match (&prev, table.get(code)) {
+                (_, Some(e)) => e.clone(),
+                (Some(p), None) => {
+                    let mut e = p.clone();
+                    e.push(p[0]);
+                    e
+                }
+                (None, None) => unreachable!(),
+            };
+            if let Some(p) = prev {
+                if table.len() < 4096 {
+                    let mut new = p.clone();
+                    new.push(entry[0]);
+                    table.push(new);
+                }
+            }
+            if table.len() >= (1 << code_size) && code_size < 12 {
+                code_size += 1;
+            }
+            out.extend(&entry);
+            prev = Some(entry);
+        }
+    }
+
+    #[test]
+    fn checksums() {
+        assert_eq!(crc32(b"IEND"), 0xAE426082);
+        assert_eq!(adler32(b"Wikipedia"), 0x11E60398);
+    }
+
+    #[test]
+    fn lzw_round_trip() {
+        let pixels: Vec<u8> = (0..20000u32).map(|i| ((i * i / 7) % 4) as u8).collect();
+        assert_eq!(lzw_decode(&lzw_encode(&pixels, 2), 2), pixels);
+        let pixels: Vec<u8> = (0..50000u32).map(|i| ((i / 3) % 256) as u8).collect();
+        assert_eq!(lzw_decode(&lzw_encode(&pixels, 8), 8), pixels);
+    }
+
+    #[test]
+    fn ppm_scaled() {
+        let image = Image::from_fn(2, 1, |x, _| x as u8);
+        let mut out = Vec::new();
+        Export::new(&[[0, 0, 0], [255, 0, 0]])
+            .scale(2)
+            .write_ppm(&image, &mut out)
+            .unwrap();
+        let mut expected = b"P6\n4 2\n255\n".to_vec();
+        for _ in 0..2 {
+            expected.extend([0, 0, 0, 0, 0, 0, 255, 0, 0, 255, 0, 0]);
+        }
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn colour_outside_palette() {
+        let image = Image::from_fn(2, 2, |x, y| (x + y) as u8);
+        let export = Export::new(&[[0, 0, 0], [255, 0, 0]]);
+        let err = export.write_ppm(&image, &mut Vec::new()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(
+            err.to_string(),
+            "pixel (1, 1) is colour 2 but the palette has 2"
+        );
+        assert!(export.write_png(&image, &mut Vec::new()).is_err());
+        let frames = [Image::new(2, 2), image];
+        let err = export.write_gif(frames, Duration::ZERO, &mut Vec::new());
+        assert!(err.is_err());
+    }
+
+    // walks the block structure, so every frame's pixels come back out
+    #[test]
+    fn gif_frames() {
+        let frames: Vec<Image> = (0..3)
+            .map(|i| Image::from_fn(5, 3, |x, y| ((x + y + i) % 3) as u8))
+            .collect();
+        let mut out = Vec::new();
+        Export::new(&[[0, 0, 0], [255, 0, 0], [0, 255, 0]])
+            .write_gif(frames.clone(), Duration::from_millis(100), &mut out)
+            .unwrap();
+        assert_eq!(&out[..6], b"GIF89a");
+        assert_eq!(&out[6..10], &[5, 0, 3, 0]);
+        // four colour table entries follow the logical screen descriptor
+        let mut i = 13 + (4 * 3);
+        let mut decoded = Vec::new();
+        while out[i] != 0x3B {
+            let image = out[i] == 0x2C;
+            let min_code_size = out[i + 10];
+            i += if image { 11 } else { 2 };
+            let mut data = Vec::new();
+            while out[i] != 0 {
+                data.extend(&out[i + 1..i + 1 + out[i] as usize]);
+                i += 1 + out[i] as usize;
+            }
+            i += 1;
+            if image {
+                decoded.push(lzw_decode(&data, min_code_size));
+            }
+        }
+        assert_eq!(i, out.len() - 1);
+        let pixels: Vec<Vec<u8>> = frames.into_iter().map(|f| f.pixels).collect();
+        assert_eq!(decoded, pixels);
+    }
+
+    #[test]
+    fn png_layout() {
+        let image = Image::from_fn(3, 2, |x, y| ((x + y) % 2) as u8);
+        let mut out = Vec::new();
+        Export::new(&[[0, 0, 0], [255, 255, 255]])
+            .write_png(&image, &mut out)
+            .unwrap();
+        assert_eq!(&out[..8], b"\x89PNG\r\n\x1a\n");
+        assert_eq!(&out[12..16], b"IHDR");
+        assert_eq!(&out[16..24], &[0, 0, 0, 3, 0, 0, 0, 2]);
+        assert_eq!(&out[out.len() - 12..], b"\0\0\0\0IEND\xAE\x42\x60\x82");
+    }
+}
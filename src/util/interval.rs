@@ -0,0 +1,203 @@
+#![allow(dead_code)]
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    ops::Range,
+};
+
+/// Set of `usize` positions stored as disjoint half-open spans. Overlapping or
+/// touching spans are merged on insert.
+///
+/// Spans are indexed by length as well as by start, so fit searches only look
+/// at one candidate per distinct span length.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IntervalSet {
+    by_start: BTreeMap<usize, usize>,
+    by_len: BTreeMap<usize, BTreeSet<usize>>,
+    total: usize,
+}
+impl IntervalSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn add_span(&mut self, start: usize, end: usize) {
+        self.by_start.insert(start, end);
+        self.by_len.entry(end - start).or_default().insert(start);
+        self.total += end - start;
+    }
+
+    fn remove_span(&mut self, start: usize) -> usize {
+        let end = self.by_start.remove(&start).unwrap();
+        let starts = self.by_len.get_mut(&(end - start)).unwrap();
+        starts.remove(&start);
+        if starts.is_empty() {
+            self.by_len.remove(&(end - start));
+        }
+        self.total -= end - start;
+        end
+    }
+
+    pub fn insert(&mut self, range: Range<usize>) {
+        if range.is_empty() {
+            return;
+        }
+        let (mut start, mut end) = (range.start, range.end);
+        if let Some((&s, &e)) = self.by_start.range(..=start).next_back() {
+            if e >= start {
+                start = s;
+                end = end.max(e);
+                self.remove_span(s);
+            }
+        }
+        while let Some((&s, &e)) = self.by_start.range(start..=end).next() {
+            end = end.max(e);
+            self.remove_span(s);
+        }
+        self.add_span(start, end);
+    }
+
+    pub fn remove(&mut self, range: Range<usize>) {
+        if range.is_empty() {
+            return;
+        }
+        let mut overlapping: Vec<usize> = self
+            .by_start
+            .range(range.start..range.end)
+            .map(|(s, _)| *s)
+            .collect();
+        if let Some((&s, &e)) = self.by_start.range(..range.start).next_back() {
+            if e > range.start {
+                overlapping.push(s);
+            }
+        }
+        for s in overlapping {
+            let e = self.remove_span(s);
+            if s < range.start {
+                self.add_span(s, range.start);
+            }
+            if e > range.end {
+                self.add_span(range.end, e);
+            }
+        }
+    }
+
+    pub fn contains(&self, point: usize) -> bool {
+        self.by_start
+            .range(..=point)
+            .next_back()
+            .is_some_and(|(_, e)| *e > point)
+    }
+
+    /// The leftmost `len` long range inside a single span.
+    pub fn first_fit(&self, len: usize) -> Option<Range<usize>> {
+        self.first_fit_below(len, usize::MAX)
+    }
+
+    /// Like `first_fit`, but the range must end at or before `limit`.
+    pub fn first_fit_below(&self, len: usize, limit: usize) -> Option<Range<usize>> {
+        self.by_len
+            .range(len.max(1)..)
+            .map(|(_, starts)| *starts.first().unwrap())
+            .filter(|s| s.saturating_add(len) <= limit)
+            .min()
+            .map(|s| s..s + len)
+    }
+
+    /// A `len` long range at the start of the smallest span that can hold it,
+    /// preferring the leftmost span on ties.
+    pub fn best_fit(&self, len: usize) -> Option<Range<usize>> {
+        self.by_len
+            .range(len.max(1)..)
+            .next()
+            .map(|(_, starts)| *starts.first().unwrap())
+            .map(|s| s..s + len)
+    }
+
+    pub fn total_len(&self) -> usize {
+        self.total
+    }
+
+    pub fn span_count(&self) -> usize {
+        self.by_start.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_start.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Range<usize>> + '_ {
+        self.by_start.iter().map(|(s, e)| *s..*e)
+    }
+
+    pub fn union(&mut self, other: &IntervalSet) {
+        for range in other.iter() {
+            self.insert(range);
+        }
+    }
+
+    /// Moves everything at or after `at` into a new set, splitting a span that crosses it.
+    pub fn split_off(&mut self, at: usize) -> IntervalSet {
+        let mut upper = IntervalSet::new();
+        let moved: Vec<Range<usize>> = self.iter().filter(|r| r.end > at).collect();
+        for range in moved {
+            self.remove(range.start.max(at)..range.end);
+            upper.insert(range.start.max(at)..range.end);
+        }
+        upper
+    }
+}
+impl FromIterator<Range<usize>> for IntervalSet {
+    fn from_iter<T: IntoIterator<Item = Range<usize>>>(iter: T) -> Self {
+        let mut set = IntervalSet::new();
+        for range in iter {
+            set.insert(range);
+        }
+        set
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::IntervalSet;
+
+    #[test]
+    fn insert_merges() {
+        let mut set: IntervalSet = [0..2, 5..7, 2..3, 10..12, 6..11].into_iter().collect();
+        assert_eq!(set.iter().collect::<Vec<_>>(), [0..3, 5..12]);
+        assert_eq!(set.total_len(), 10);
+        set.insert(3..5);
+        assert_eq!((set.span_count(), set.iter().next()), (1, Some(0..12)));
+    }
+
+    #[test]
+    fn remove_splits() {
+        let mut set: IntervalSet = [0..10, 20..30].into_iter().collect();
+        set.remove(3..5);
+        set.remove(8..22);
+        assert_eq!(set.iter().collect::<Vec<_>>(), [0..3, 5..8, 22..30]);
+        assert!(set.contains(5) && !set.contains(4) && !set.contains(8));
+        assert_eq!(set.total_len(), 14);
+    }
+
+    #[test]
+    fn fits() {
+        let set: IntervalSet = [0..2, 4..9, 12..15, 20..24].into_iter().collect();
+        assert_eq!(set.first_fit(3), Some(4..7));
+        assert_eq!(set.best_fit(3), Some(12..15));
+        assert_eq!(set.best_fit(4), Some(20..24));
+        assert_eq!(set.first_fit_below(3, 7), Some(4..7));
+        assert_eq!(set.first_fit_below(3, 6), None);
+        assert_eq!(set.first_fit(6), None);
+    }
+
+    #[test]
+    fn split_and_union() {
+        let mut set: IntervalSet = [0..5, 8..12].into_iter().collect();
+        let upper = set.split_off(10);
+        assert_eq!(set.iter().collect::<Vec<_>>(), [0..5, 8..10]);
+        assert_eq!((upper.span_count(), upper.iter().next()), (1, Some(10..12)));
+        set.union(&upper);
+        assert_eq!(set.iter().collect::<Vec<_>>(), [0..5, 8..12]);
+    }
+}
@@ -0,0 +1,321 @@
+#![allow(dead_code)]
+
+// The i64 functions are the everyday interface. They're built on the checked
+// i128 versions, which return Err(Overflow) instead of silently wrapping.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Overflow;
+
+pub fn gcd(a: i64, b: i64) -> i64 {
+    checked_gcd(a.into(), b.into()).unwrap().try_into().unwrap()
+}
+
+pub fn lcm(a: i64, b: i64) -> i64 {
+    checked_lcm(a.into(), b.into())
+        .unwrap()
+        .try_into()
+        .expect("lcm overflows i64")
+}
+
+// returns (g, x, y) with a*x + b*y == g, g >= 0
+pub fn extended_gcd(a: i64, b: i64) -> (i64, i64, i64) {
+    let (g, x, y) = checked_extended_gcd(a.into(), b.into()).unwrap();
+    (
+        g.try_into().unwrap(),
+        x.try_into().unwrap(),
+        y.try_into().unwrap(),
+    )
+}
+
+pub fn mod_inverse(a: i64, m: i64) -> Option<i64> {
+    checked_mod_inverse(a.into(), m.into())
+        .unwrap()
+        .map(|x| x.try_into().unwrap())
+}
+
+pub fn mod_pow(base: i64, exp: u64, m: i64) -> i64 {
+    checked_mod_pow(base.into(), exp, m.into())
+        .unwrap()
+        .try_into()
+        .unwrap()
+}
+
+// congruences are (residue, modulus) pairs; returns (residue, lcm of moduli)
+pub fn crt(congruences: &[(i64, i64)]) -> Option<(i64, i64)> {
+    let wide: Vec<(i128, i128)> = congruences
+        .iter()
+        .map(|(r, m)| ((*r).into(), (*m).into()))
+        .collect();
+    checked_crt(&wide)
+        .expect("combined modulus overflows i64")
+        .map(|(r, m)| (r.try_into().unwrap(), m.try_into().unwrap()))
+}
+
+pub fn solve_diophantine(a: i64, b: i64, c: i64) -> Option<Diophantine> {
+    checked_solve_diophantine(a.into(), b.into(), c.into()).unwrap()
+}
+
+pub fn digit_count(v: usize) -> usize {
+    v.checked_ilog10().unwrap_or(0) as usize + 1
+}
+
+// a - q*b
+fn checked_sub_mul(a: i128, q: i128, b: i128) -> Result<i128, Overflow> {
+    q.checked_mul(b)
+        .and_then(|qb| a.checked_sub(qb))
+        .ok_or(Overflow)
+}
+
+pub fn checked_gcd(a: i128, b: i128) -> Result<i128, Overflow> {
+    let (mut a, mut b) = (a, b);
+    while b != 0 {
+        (a, b) = (b, a.checked_rem(b).ok_or(Overflow)?);
+    }
+    a.checked_abs().ok_or(Overflow)
+}
+
+pub fn checked_lcm(a: i128, b: i128) -> Result<i128, Overflow> {
+    if a == 0 || b == 0 {
+        return Ok(0);
+    }
+    (a / checked_gcd(a, b)?)
+        .checked_mul(b)
+        .and_then(i128::checked_abs)
+        .ok_or(Overflow)
+}
+
+pub fn checked_extended_gcd(a: i128, b: i128) -> Result<(i128, i128, i128), Overflow> {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_x, mut x) = (1i128, 0i128);
+    let (mut old_y, mut y) = (0i128, 1i128);
+    while r != 0 {
+        let q = old_r.checked_div(r).ok_or(Overflow)?;
+        (old_r, r) = (r, checked_sub_mul(old_r, q, r)?);
+        (old_x, x) = (x, checked_sub_mul(old_x, q, x)?);
+        (old_y, y) = (y, checked_sub_mul(old_y, q, y)?);
+    }
+    if old_r < 0 {
+        old_r = old_r.checked_neg().ok_or(Overflow)?;
+        old_x = old_x.checked_neg().ok_or(Overflow)?;
+        old_y = old_y.checked_neg().ok_or(Overflow)?;
+    }
+    Ok((old_r, old_x, old_y))
+}
+
+pub fn checked_mod_inverse(a: i128, m: i128) -> Result<Option<i128>, Overflow> {
+    assert!(m > 0);
+    let (g, x, _) = checked_extended_gcd(a.rem_euclid(m), m)?;
+    Ok((g == 1).then(|| x.rem_euclid(m)))
+}
+
+pub fn checked_mod_pow(base: i128, mut exp: u64, m: i128) -> Result<i128, Overflow> {
+    assert!(m > 0);
+    let mut result = 1 % m;
+    let mut base = base.rem_euclid(m);
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = result.checked_mul(base).ok_or(Overflow)? % m;
+        }
+        base = base.checked_mul(base).ok_or(Overflow)? % m;
+        exp >>= 1;
+    }
+    Ok(result)
+}
+
+// moduli need not be coprime; Ok(None) means the congruences are inconsistent
+pub fn checked_crt(congruences: &[(i128, i128)]) -> Result<Option<(i128, i128)>, Overflow> {
+    let mut acc = (0i128, 1i128);
+    for &(r2, m2) in congruences {
+        assert!(m2 > 0);
+        let (r1, m1) = acc;
+        let r2 = r2.rem_euclid(m2);
+        let (g, p, _) = checked_extended_gcd(m1, m2)?;
+        let diff = r2 - r1;
+        if diff % g != 0 {
+            return Ok(None);
+        }
+        let step = m2 / g;
+        // p is the inverse of m1/g modulo m2/g
+        let k = (diff / g)
+            .rem_euclid(step)
+            .checked_mul(p.rem_euclid(step))
+            .ok_or(Overflow)?;
+        let k = k.rem_euclid(step);
+        let lcm = m1.checked_mul(step).ok_or(Overflow)?;
+        let r = r1
+            .checked_add(m1.checked_mul(k).ok_or(Overflow)?)
+            .ok_or(Overflow)?
+            .rem_euclid(lcm);
+        acc = (r, lcm);
+    }
+    Ok(Some(acc))
+}
+
+/// All integer solutions of `a*x + b*y = c`: `(x + k*dx, y - k*dy)` for any integer `k`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Diophantine {
+    pub x: i64,
+    pub y: i64,
+    pub dx: i64,
+    pub dy: i64,
+}
+impl Diophantine {
+    pub fn at(&self, k: i64) -> (i64, i64) {
+        (self.x + k * self.dx, self.y - k * self.dy)
+    }
+}
+
+pub fn checked_solve_diophantine(
+    a: i128,
+    b: i128,
+    c: i128,
+) -> Result<Option<Diophantine>, Overflow> {
+    if a == 0 && b == 0 {
+        return Ok(None);
+    }
+    let (g, x, y) = checked_extended_gcd(a, b)?;
+    if c % g != 0 {
+        return Ok(None);
+    }
+    let scale = c / g;
+    let narrow = |v: i128| -> Result<i64, Overflow> { v.try_into().map_err(|_| Overflow) };
+    Ok(Some(Diophantine {
+        x: narrow(x.checked_mul(scale).ok_or(Overflow)?)?,
+        y: narrow(y.checked_mul(scale).ok_or(Overflow)?)?,
+        dx: narrow(b / g)?,
+        dy: narrow(a / g)?,
+    }))
+}
+
+/// Exact fraction in lowest terms with a positive denominator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rational {
+    pub num: i128,
+    pub den: i128,
+}
+impl Rational {
+    pub fn new(num: i128, den: i128) -> Self {
+        Self::checked_new(num, den).expect("fraction overflows i128")
+    }
+
+    pub fn checked_new(num: i128, den: i128) -> Result<Self, Overflow> {
+        assert_ne!(den, 0);
+        let g = checked_extended_gcd(num, den)?.0;
+        let sign = if den < 0 { -1 } else { 1 };
+        Ok(Rational {
+            num: (num / g).checked_mul(sign).ok_or(Overflow)?,
+            den: (den / g).checked_mul(sign).ok_or(Overflow)?,
+        })
+    }
+
+    pub fn as_integer(&self) -> Option<i128> {
+        (self.den == 1).then_some(self.num)
+    }
+}
+
+// solves a1*x + b1*y = c1, a2*x + b2*y = c2; None when the system is singular
+// or (which never happens for i64 coefficients) a product overflows
+pub fn solve_2x2(eq1: (i64, i64, i64), eq2: (i64, i64, i64)) -> Option<(Rational, Rational)> {
+    let wide = |(a, b, c): (i64, i64, i64)| (a.into(), b.into(), c.into());
+    checked_solve_2x2(wide(eq1), wide(eq2)).ok().flatten()
+}
+
+pub fn checked_solve_2x2(
+    (a1, b1, c1): (i128, i128, i128),
+    (a2, b2, c2): (i128, i128, i128),
+) -> Result<Option<(Rational, Rational)>, Overflow> {
+    // p*q - r*s
+    let cross = |p: i128, q: i128, r: i128, s: i128| {
+        p.checked_mul(q)
+            .and_then(|pq| pq.checked_sub(r.checked_mul(s)?))
+            .ok_or(Overflow)
+    };
+    let det = cross(a1, b2, a2, b1)?;
+    if det == 0 {
+        return Ok(None);
+    }
+    let x = Rational::checked_new(cross(c1, b2, c2, b1)?, det)?;
+    let y = Rational::checked_new(cross(a1, c2, a2, c1)?, det)?;
+    Ok(Some((x, y)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        checked_crt, checked_gcd, checked_lcm, checked_mod_pow, checked_solve_2x2, crt,
+        digit_count, extended_gcd, gcd, lcm, mod_inverse, mod_pow, solve_2x2, solve_diophantine,
+        Overflow, Rational,
+    };
+
+    #[test]
+    fn gcd_and_lcm() {
+        assert_eq!(gcd(48, -18), 6);
+        assert_eq!(lcm(101, 103), 10403);
+        assert_eq!(lcm(4, 6), 12);
+        let (g, x, y) = extended_gcd(240, 46);
+        assert_eq!(g, 2);
+        assert_eq!(240 * x + 46 * y, 2);
+        // past i64, and past i128
+        assert_eq!(
+            checked_lcm(i64::MAX.into(), (i64::MAX - 1).into()),
+            Ok(i128::from(i64::MAX) * i128::from(i64::MAX - 1))
+        );
+        assert_eq!(checked_lcm(i128::MAX, i128::MAX - 1), Err(Overflow));
+        assert_eq!(checked_gcd(i128::MIN, 0), Err(Overflow));
+    }
+
+    #[test]
+    fn modular() {
+        assert_eq!(mod_inverse(3, 11), Some(4));
+        assert_eq!(mod_inverse(6, 9), None);
+        assert_eq!(mod_pow(2, 10, 1000), 24);
+        assert_eq!(mod_pow(-2, 3, 5), 2);
+        assert_eq!(checked_mod_pow(i128::MAX - 1, 2, i128::MAX), Err(Overflow));
+    }
+
+    #[test]
+    fn chinese_remainder() {
+        assert_eq!(crt(&[(2, 3), (3, 5), (2, 7)]), Some((23, 105)));
+        // non-coprime moduli
+        assert_eq!(crt(&[(2, 4), (4, 6)]), Some((10, 12)));
+        assert_eq!(crt(&[(1, 4), (2, 6)]), None);
+        assert_eq!(
+            checked_crt(&[(0, i128::MAX), (1, i128::MAX - 1)]),
+            Err(Overflow)
+        );
+    }
+
+    #[test]
+    fn diophantine() {
+        let sol = solve_diophantine(94, 22, 8400).unwrap();
+        for k in -3..3 {
+            let (x, y) = sol.at(k);
+            assert_eq!(94 * x + 22 * y, 8400);
+        }
+        assert_eq!(solve_diophantine(4, 6, 7), None);
+    }
+
+    #[test]
+    fn exact_2x2() {
+        let (a, b) = solve_2x2((94, 22, 8400), (34, 67, 5400)).unwrap();
+        assert_eq!((a.as_integer(), b.as_integer()), (Some(80), Some(40)));
+        let (a, _) = solve_2x2((26, 67, 12748), (66, 21, 12176)).unwrap();
+        assert_eq!(a.as_integer(), None);
+        assert_eq!(Rational::new(6, -4), Rational { num: -3, den: 2 });
+        assert_eq!(solve_2x2((1, 2, 3), (2, 4, 6)), None);
+        let big = i128::MAX / 2;
+        assert_eq!(checked_solve_2x2((big, 1, 0), (1, big, 0)), Err(Overflow));
+        assert_eq!(
+            checked_solve_2x2((1, 0, big), (0, 1, -big)),
+            Ok(Some((Rational::new(big, 1), Rational::new(-big, 1))))
+        );
+    }
+
+    #[test]
+    fn digits() {
+        assert_eq!(digit_count(0), 1);
+        assert_eq!(digit_count(9), 1);
+        assert_eq!(digit_count(10), 2);
+        assert_eq!(digit_count(253000), 6);
+    }
+}
@@ -0,0 +1,129 @@
+#![allow(dead_code)]
+
+use std::{collections::HashMap, hash::Hash};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct MemoStats {
+    pub hits: usize,
+    pub misses: usize,
+    pub entries: usize,
+}
+
+/// Cache for recursive solvers, keyed by the arguments that determine the result.
+///
+/// With a size bound, results computed once the cache is full are returned but
+/// not stored, so memory stays capped at the cost of some recomputation.
+pub struct Memo<K, V> {
+    cache: HashMap<K, V>,
+    bound: Option<usize>,
+    hits: usize,
+    misses: usize,
+}
+impl<K: Hash + Eq, V: Clone> Memo<K, V> {
+    pub fn new() -> Self {
+        Memo {
+            cache: HashMap::new(),
+            bound: None,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    pub fn bounded(bound: usize) -> Self {
+        Memo {
+            cache: HashMap::new(),
+            bound: Some(bound),
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    pub fn get(&mut self, key: &K) -> Option<V> {
+        match self.cache.get(key) {
+            Some(v) => {
+                self.hits += 1;
+                Some(v.clone())
+            }
+            None => {
+                self.misses += 1;
+                None
+            }
+        }
+    }
+
+    pub fn insert(&mut self, key: K, val: V) {
+        if self.bound.is_some_and(|bound| self.cache.len() >= bound)
+            && !self.cache.contains_key(&key)
+        {
+            return;
+        }
+        self.cache.insert(key, val);
+    }
+
+    /// Returns the cached value for `key`, or computes it with `f` and caches it.
+    /// `f` gets the memo back so it can recurse.
+    pub fn get_or_compute(&mut self, key: K, f: impl FnOnce(&mut Self) -> V) -> V {
+        if let Some(v) = self.get(&key) {
+            return v;
+        }
+        let v = f(self);
+        self.insert(key, v.clone());
+        v
+    }
+
+    pub fn stats(&self) -> MemoStats {
+        MemoStats {
+            hits: self.hits,
+            misses: self.misses,
+            entries: self.cache.len(),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.cache.clear();
+        self.hits = 0;
+        self.misses = 0;
+    }
+}
+impl<K: Hash + Eq, V: Clone> Default for Memo<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Memo, MemoStats};
+
+    fn fib(memo: &mut Memo<u64, u64>, n: u64) -> u64 {
+        if n < 2 {
+            return n;
+        }
+        memo.get_or_compute(n, |memo| fib(memo, n - 1) + fib(memo, n - 2))
+    }
+
+    #[test]
+    fn recursive_fib() {
+        let mut memo = Memo::new();
+        assert_eq!(fib(&mut memo, 90), 2880067194370816120);
+        assert_eq!(
+            memo.stats(),
+            MemoStats {
+                hits: 87,
+                misses: 89,
+                entries: 89
+            }
+        );
+    }
+
+    #[test]
+    fn bounded_stops_storing() {
+        let mut memo = Memo::bounded(10);
+        assert_eq!(fib(&mut memo, 30), 832040);
+        assert_eq!(memo.stats().entries, 10);
+
+        // existing keys can still be overwritten when full
+        memo.insert(2, 100);
+        assert_eq!(memo.get(&2), Some(100));
+    }
+}
@@ -0,0 +1,13 @@
+pub mod bigint;
+pub mod bitgrid;
+pub mod checkpoint;
+pub mod image;
+pub mod interval;
+pub mod math;
+pub mod memo;
+pub mod parallel;
+pub mod search;
+pub mod snapshot;
+pub mod sparse;
+pub mod term;
+pub mod validate;
@@ -0,0 +1,186 @@
+#![allow(dead_code)]
+
+use std::{
+    num::NonZeroUsize,
+    ops::Range,
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+};
+
+// indices one worker checks at a time when searching for the first match
+const SPAN: usize = 4096;
+
+pub fn available_threads() -> usize {
+    thread::available_parallelism().map_or(1, NonZeroUsize::get)
+}
+
+/// Splits a slice into batches that worker threads claim from a shared counter,
+/// so a slow batch doesn't leave the other workers idle. Batch results are
+/// combined in input order, so answers don't depend on the thread count.
+#[derive(Debug, Clone, Copy)]
+pub struct Parallel {
+    threads: usize,
+}
+impl Parallel {
+    pub fn new() -> Self {
+        Parallel {
+            threads: available_threads(),
+        }
+    }
+
+    pub fn threads(threads: usize) -> Self {
+        Parallel {
+            threads: threads.max(1),
+        }
+    }
+
+    // a few batches per thread keeps everyone busy when items vary in cost
+    fn batch_size(&self, len: usize) -> usize {
+        len.div_ceil(self.threads * 8).max(1)
+    }
+
+    fn run_batches<T, S, B>(
+        &self,
+        items: &[T],
+        init: impl Fn() -> S + Sync,
+        per_batch: impl Fn(&mut S, &[T]) -> B + Sync,
+    ) -> Vec<B>
+    where
+        T: Sync,
+        B: Send,
+    {
+        let batch = self.batch_size(items.len());
+        let batches = items.len().div_ceil(batch);
+        let next = AtomicUsize::new(0);
+        let mut done: Vec<(usize, B)> = thread::scope(|scope| {
+            let handles: Vec<_> = (0..self.threads.min(batches))
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut scratch = init();
+                        let mut out = Vec::new();
+                        loop {
+                            let b = next.fetch_add(1, Ordering::Relaxed);
+                            if b >= batches {
+                                return out;
+                            }
+                            let end = ((b + 1) * batch).min(items.len());
+                            out.push((b, per_batch(&mut scratch, &items[b * batch..end])));
+                        }
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .flat_map(|h| h.join().unwrap())
+                .collect()
+        });
+        done.sort_unstable_by_key(|(b, _)| *b);
+        done.into_iter().map(|(_, out)| out).collect()
+    }
+
+    /// One result per item, in order.
+    pub fn map<T: Sync, R: Send>(&self, items: &[T], f: impl Fn(&T) -> R + Sync) -> Vec<R> {
+        self.map_with(items, || (), |_, item| f(item))
+    }
+
+    /// Like `map`, but each worker gets its own scratch state from `init`.
+    pub fn map_with<T: Sync, S, R: Send>(
+        &self,
+        items: &[T],
+        init: impl Fn() -> S + Sync,
+        f: impl Fn(&mut S, &T) -> R + Sync,
+    ) -> Vec<R> {
+        self.run_batches(items, init, |scratch, batch| {
+            batch
+                .iter()
+                .map(|item| f(scratch, item))
+                .collect::<Vec<_>>()
+        })
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+
+    /// `reduce` is applied in input order, so it only needs to be associative.
+    /// `None` for an empty slice.
+    pub fn map_reduce<T: Sync, R: Send>(
+        &self,
+        items: &[T],
+        map: impl Fn(&T) -> R + Sync,
+        reduce: impl Fn(R, R) -> R + Sync,
+    ) -> Option<R> {
+        self.run_batches(
+            items,
+            || (),
+            |_, batch| batch.iter().map(&map).reduce(&reduce),
+        )
+        .into_iter()
+        .flatten()
+        .reduce(&reduce)
+    }
+
+    /// The lowest index from `start` up that satisfies `pred`. Never returns if
+    /// there isn't one.
+    pub fn first_index(&self, start: usize, pred: impl Fn(usize) -> bool + Sync) -> usize {
+        let round = self.threads * 4 * SPAN;
+        let mut round_start = start;
+        loop {
+            if let Some(i) = self.first_index_in(round_start..round_start + round, &pred) {
+                return i;
+            }
+            round_start += round;
+        }
+    }
+
+    /// The lowest index in `range` that satisfies `pred`.
+    pub fn first_index_in(
+        &self,
+        range: Range<usize>,
+        pred: impl Fn(usize) -> bool + Sync,
+    ) -> Option<usize> {
+        let spans: Vec<usize> = range.clone().step_by(SPAN).collect();
+        self.map_reduce(
+            &spans,
+            |s| (*s..(*s + SPAN).min(range.end)).find(|i| pred(*i)),
+            |a, b| a.or(b),
+        )
+        .flatten()
+    }
+}
+impl Default for Parallel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Parallel;
+
+    #[test]
+    fn same_result_for_any_thread_count() {
+        let items: Vec<u64> = (0..1000).collect();
+        for threads in [1, 3, 8, 64] {
+            let pool = Parallel::threads(threads);
+            assert_eq!(pool.map(&items, |x| x * 2)[999], 1998);
+            let concat = pool.map_reduce(&items[..20], |x| x.to_string(), |a, b| a + &b);
+            assert_eq!(concat.unwrap(), "012345678910111213141516171819");
+            assert_eq!(pool.first_index(10, |i| i % 5000 == 4999), 4999);
+            assert_eq!(pool.first_index_in(5000..9999, |i| i % 5000 == 4999), None);
+        }
+    }
+
+    #[test]
+    fn fewer_items_than_threads() {
+        let pool = Parallel::threads(16);
+        assert_eq!(pool.map_reduce(&[1, 2, 3], |x| *x, |a, b| a + b), Some(6));
+        assert_eq!(pool.map_reduce(&[] as &[u32], |x| *x, |a, b| a + b), None);
+        assert_eq!(pool.map(&[5, 6], |x| x + 1), [6, 7]);
+        // one worker keeps its scratch across every batch
+        let counts = Parallel::threads(1).map_with(&[5, 6], Vec::new, |seen, x| {
+            seen.push(*x);
+            seen.len()
+        });
+        assert_eq!(counts, [1, 2]);
+    }
+}
@@ -0,0 +1,268 @@
+#![allow(dead_code)]
+
+use std::{
+    collections::{HashSet, VecDeque},
+    hash::Hash,
+    ops::AddAssign,
+    thread,
+};
+
+/// A node in a search tree.
+pub trait SearchState: Clone + Eq + Hash + Send {
+    fn is_goal(&self) -> bool;
+
+    /// Pushes the states reachable from this one, in the order they should be tried.
+    fn successors(&self, out: &mut Vec<Self>);
+
+    /// Returning true discards this state and everything below it.
+    fn prune(&self) -> bool {
+        false
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+    DepthFirst,
+    BreadthFirst,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SearchStats {
+    pub expanded: usize,
+    pub pruned: usize,
+    pub revisited: usize,
+}
+impl AddAssign for SearchStats {
+    fn add_assign(&mut self, rhs: Self) {
+        self.expanded += rhs.expanded;
+        self.pruned += rhs.pruned;
+        self.revisited += rhs.revisited;
+    }
+}
+
+pub struct SearchResult<S> {
+    pub solution: Option<S>,
+    pub depth: usize,
+    pub stats: SearchStats,
+}
+
+pub struct Search {
+    strategy: Strategy,
+    remember_visited: bool,
+    parallel: bool,
+}
+impl Search {
+    pub fn new(strategy: Strategy) -> Self {
+        Search {
+            strategy,
+            remember_visited: false,
+            parallel: false,
+        }
+    }
+
+    /// Skips states that have already been seen. Only worth it when the same
+    /// state is reachable along different paths.
+    pub fn remember_visited(self) -> Self {
+        Search {
+            remember_visited: true,
+            ..self
+        }
+    }
+
+    /// Searches below each successor of the root on its own thread. Each thread
+    /// keeps its own visited set. The solution is the one a sequential search
+    /// would find.
+    pub fn parallel(self) -> Self {
+        Search {
+            parallel: true,
+            ..self
+        }
+    }
+
+    pub fn run<S: SearchState>(&self, root: S) -> SearchResult<S> {
+        if !self.parallel {
+            return self.run_from(root, 0);
+        }
+
+        let mut stats = SearchStats::default();
+        if root.prune() {
+            stats.pruned += 1;
+            return SearchResult {
+                solution: None,
+                depth: 0,
+                stats,
+            };
+        }
+        if root.is_goal() {
+            return SearchResult {
+                solution: Some(root),
+                depth: 0,
+                stats,
+            };
+        }
+        let mut children = Vec::new();
+        root.successors(&mut children);
+        stats.expanded += 1;
+
+        let results: Vec<SearchResult<S>> = thread::scope(|scope| {
+            let handles: Vec<_> = children
+                .into_iter()
+                .map(|child| scope.spawn(move || self.run_from(child, 1)))
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+
+        let mut best: Option<(S, usize)> = None;
+        for result in results {
+            stats += result.stats;
+            if let Some(solution) = result.solution {
+                // depth first takes the first subtree with an answer, breadth first the shallowest
+                let better = match (&best, self.strategy) {
+                    (None, _) => true,
+                    (Some(_), Strategy::DepthFirst) => false,
+                    (Some((_, depth)), Strategy::BreadthFirst) => result.depth < *depth,
+                };
+                if better {
+                    best = Some((solution, result.depth));
+                }
+            }
+        }
+        let depth = best.as_ref().map_or(0, |(_, depth)| *depth);
+        SearchResult {
+            solution: best.map(|(s, _)| s),
+            depth,
+            stats,
+        }
+    }
+
+    fn run_from<S: SearchState>(&self, root: S, depth: usize) -> SearchResult<S> {
+        let mut stats = SearchStats::default();
+        let mut frontier = VecDeque::from([(root, depth)]);
+        let mut visited = HashSet::new();
+        let mut buffer = Vec::new();
+        loop {
+            let next = match self.strategy {
+                Strategy::DepthFirst => frontier.pop_back(),
+                Strategy::BreadthFirst => frontier.pop_front(),
+            };
+            let Some((state, depth)) = next else {
+                return SearchResult {
+                    solution: None,
+                    depth: 0,
+                    stats,
+                };
+            };
+            if self.remember_visited && !visited.insert(state.clone()) {
+                stats.revisited += 1;
+                continue;
+            }
+            if state.prune() {
+                stats.pruned += 1;
+                continue;
+            }
+            if state.is_goal() {
+                return SearchResult {
+                    solution: Some(state),
+                    depth,
+                    stats,
+                };
+            }
+            state.successors(&mut buffer);
+            stats.expanded += 1;
+            // depth first pushes in reverse so the first successor is popped first
+            match self.strategy {
+                Strategy::DepthFirst => {
+                    frontier.extend(buffer.drain(..).rev().map(|s| (s, depth + 1)))
+                }
+                Strategy::BreadthFirst => frontier.extend(buffer.drain(..).map(|s| (s, depth + 1))),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Search, SearchState, SearchStats, Strategy};
+
+    #[derive(Clone, PartialEq, Eq, Hash)]
+    struct Queens(Vec<usize>);
+    impl SearchState for Queens {
+        fn is_goal(&self) -> bool {
+            self.0.len() == 6
+        }
+
+        fn successors(&self, out: &mut Vec<Self>) {
+            for col in 0..6 {
+                let mut next = self.0.clone();
+                next.push(col);
+                out.push(Queens(next));
+            }
+        }
+
+        fn prune(&self) -> bool {
+            let Some((last, rest)) = self.0.split_last() else {
+                return false;
+            };
+            let row = rest.len();
+            rest.iter()
+                .enumerate()
+                .any(|(r, c)| c == last || c.abs_diff(*last) == row - r)
+        }
+    }
+
+    #[derive(Clone, PartialEq, Eq, Hash)]
+    struct Reach(u32);
+    impl SearchState for Reach {
+        fn is_goal(&self) -> bool {
+            self.0 == 10
+        }
+
+        fn successors(&self, out: &mut Vec<Self>) {
+            out.push(Reach(self.0 + 1));
+            out.push(Reach(self.0 * 2));
+        }
+
+        fn prune(&self) -> bool {
+            self.0 > 10
+        }
+    }
+
+    #[test]
+    fn depth_first_finds_first_in_order() {
+        let result = Search::new(Strategy::DepthFirst).run(Queens(Vec::new()));
+        assert_eq!(result.solution.unwrap().0, [1, 3, 5, 0, 2, 4]);
+        let parallel = Search::new(Strategy::DepthFirst)
+            .parallel()
+            .run(Queens(Vec::new()));
+        assert_eq!(parallel.solution.unwrap().0, [1, 3, 5, 0, 2, 4]);
+        assert!(parallel.stats.pruned > 0);
+    }
+
+    #[test]
+    fn breadth_first_finds_shallowest() {
+        let result = Search::new(Strategy::BreadthFirst).run(Reach(1));
+        assert_eq!(result.depth, 4);
+        let remembered = Search::new(Strategy::BreadthFirst)
+            .remember_visited()
+            .run(Reach(1));
+        assert_eq!(remembered.depth, 4);
+        assert!(remembered.stats.revisited > 0);
+        assert!(remembered.stats.expanded < result.stats.expanded);
+        let parallel = Search::new(Strategy::BreadthFirst).parallel().run(Reach(1));
+        assert_eq!(parallel.depth, 4);
+    }
+
+    #[test]
+    fn exhausted_search() {
+        let result = Search::new(Strategy::DepthFirst).run(Reach(11));
+        assert!(result.solution.is_none());
+        assert_eq!(
+            result.stats,
+            SearchStats {
+                expanded: 0,
+                pruned: 1,
+                revisited: 0
+            }
+        );
+    }
+}
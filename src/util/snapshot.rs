@@ -0,0 +1,119 @@
+#![allow(dead_code)]
+
+use std::{env, fs, path::Path};
+
+use super::term::{Cell, Color, Frame, Style};
+
+const UPDATE_VAR: &str = "UPDATE_SNAPSHOTS";
+
+// only the first few differences are listed, the highlighted grid shows the rest
+const LISTED_DIFFS: usize = 10;
+
+/// Compares `actual` against `snapshots/<name>.snap`, panicking with a cell
+/// level diff if they don't match. With `UPDATE_SNAPSHOTS=1` set the file is
+/// (re)written instead.
+pub fn assert_snapshot(name: &str, actual: &str) {
+    let path = format!("snapshots/{name}.snap");
+    if env::var_os(UPDATE_VAR).is_some() {
+        if let Some(dir) = Path::new(&path).parent() {
+            fs::create_dir_all(dir).unwrap();
+        }
+        fs::write(&path, actual).unwrap();
+        return;
+    }
+    let Ok(expected) = fs::read_to_string(&path) else {
+        panic!("no snapshot at {path}, run with {UPDATE_VAR}=1 to create it");
+    };
+    if let Some(diff) = diff_grids(&expected, actual) {
+        panic!("{path} doesn't match, run with {UPDATE_VAR}=1 to accept the change\n{diff}");
+    }
+}
+
+/// `None` if the two grids are identical. Otherwise the actual grid with the
+/// changed cells highlighted, followed by the first few differences.
+pub fn diff_grids(expected: &str, actual: &str) -> Option<String> {
+    if expected == actual {
+        return None;
+    }
+    let expected: Vec<Vec<char>> = expected.lines().map(|l| l.chars().collect()).collect();
+    let actual: Vec<Vec<char>> = actual.lines().map(|l| l.chars().collect()).collect();
+    let height = expected.len().max(actual.len());
+    let width = expected
+        .iter()
+        .chain(actual.iter())
+        .map(|row| row.len())
+        .max()
+        .unwrap_or(0);
+    let at =
+        |grid: &[Vec<char>], x: usize, y: usize| grid.get(y).and_then(|row| row.get(x)).copied();
+
+    let mut changed = Vec::new();
+    for y in 0..height {
+        for x in 0..width {
+            let (e, a) = (at(&expected, x, y), at(&actual, x, y));
+            if e != a {
+                changed.push((x, y, e, a));
+            }
+        }
+    }
+    // only line endings differ
+    if changed.is_empty() {
+        return Some("grids match cell for cell but differ in trailing whitespace".to_string());
+    }
+
+    let mut frame = Frame::from_fn(width, height, |x, y| {
+        Cell::plain(at(&actual, x, y).unwrap_or(' '))
+    });
+    frame.highlight(
+        changed.iter().map(|(x, y, _, _)| (*x, *y)),
+        Style::fg(Color::White).on(Color::Red).bold(),
+    );
+    frame.caption(format!(
+        "{} cells differ (expected {}x{}, got {}x{})",
+        changed.len(),
+        expected.iter().map(|row| row.len()).max().unwrap_or(0),
+        expected.len(),
+        actual.iter().map(|row| row.len()).max().unwrap_or(0),
+        actual.len(),
+    ));
+    let mut out = frame.to_ansi_string();
+    let show = |c: Option<char>| c.map_or("nothing".to_string(), |c| format!("{c:?}"));
+    for (x, y, e, a) in changed.iter().take(LISTED_DIFFS) {
+        out.push_str(&format!(
+            "  ({x}, {y}): expected {}, got {}\n",
+            show(*e),
+            show(*a)
+        ));
+    }
+    if changed.len() > LISTED_DIFFS {
+        out.push_str(&format!(
+            "  ... and {} more\n",
+            changed.len() - LISTED_DIFFS
+        ));
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::diff_grids;
+
+    #[test]
+    fn identical_grids() {
+        assert_eq!(diff_grids("#.#\n...\n", "#.#\n...\n"), None);
+    }
+
+    #[test]
+    fn lists_changed_cells() {
+        let diff = diff_grids("#.#\n...\n", "#O#\n..\n").unwrap();
+        assert!(diff.contains("2 cells differ (expected 3x2, got 3x2)"));
+        assert!(diff.contains("(1, 0): expected '.', got 'O'"));
+        assert!(diff.contains("(2, 1): expected '.', got nothing"));
+    }
+
+    #[test]
+    fn whitespace_only() {
+        let diff = diff_grids("ab\n", "ab").unwrap();
+        assert!(diff.contains("trailing whitespace"));
+    }
+}
@@ -0,0 +1,198 @@
+#![allow(dead_code)]
+
+use std::collections::{hash_map, HashMap};
+
+use super::image::Image;
+
+/// Inclusive bounding box of signed coordinates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bounds {
+    pub min_x: i64,
+    pub max_x: i64,
+    pub min_y: i64,
+    pub max_y: i64,
+}
+impl Bounds {
+    fn point(x: i64, y: i64) -> Self {
+        Bounds {
+            min_x: x,
+            max_x: x,
+            min_y: y,
+            max_y: y,
+        }
+    }
+
+    fn extend(&mut self, x: i64, y: i64) {
+        self.min_x = self.min_x.min(x);
+        self.max_x = self.max_x.max(x);
+        self.min_y = self.min_y.min(y);
+        self.max_y = self.max_y.max(y);
+    }
+
+    pub fn contains(&self, x: i64, y: i64) -> bool {
+        (self.min_x..=self.max_x).contains(&x) && (self.min_y..=self.max_y).contains(&y)
+    }
+
+    pub fn width(&self) -> usize {
+        (self.max_x - self.min_x + 1) as usize
+    }
+
+    pub fn height(&self) -> usize {
+        (self.max_y - self.min_y + 1) as usize
+    }
+}
+
+/// Grid on the infinite plane: only occupied cells are stored, and the
+/// bounding box follows whatever has been inserted.
+#[derive(Debug, Clone)]
+pub struct SparseGrid<T> {
+    cells: HashMap<(i64, i64), T>,
+    bounds: Option<Bounds>,
+}
+impl<T> SparseGrid<T> {
+    pub fn new() -> Self {
+        SparseGrid {
+            cells: HashMap::new(),
+            bounds: None,
+        }
+    }
+
+    fn touch(&mut self, x: i64, y: i64) {
+        match &mut self.bounds {
+            Some(bounds) => bounds.extend(x, y),
+            None => self.bounds = Some(Bounds::point(x, y)),
+        }
+    }
+
+    pub fn insert(&mut self, x: i64, y: i64, val: T) -> Option<T> {
+        self.touch(x, y);
+        self.cells.insert((x, y), val)
+    }
+
+    pub fn get(&self, x: i64, y: i64) -> Option<&T> {
+        self.cells.get(&(x, y))
+    }
+
+    pub fn get_mut(&mut self, x: i64, y: i64) -> Option<&mut T> {
+        self.cells.get_mut(&(x, y))
+    }
+
+    /// The cell counts toward the bounding box even if the entry is left vacant.
+    pub fn entry(&mut self, x: i64, y: i64) -> hash_map::Entry<'_, (i64, i64), T> {
+        self.touch(x, y);
+        self.cells.entry((x, y))
+    }
+
+    pub fn contains(&self, x: i64, y: i64) -> bool {
+        self.cells.contains_key(&(x, y))
+    }
+
+    pub fn remove(&mut self, x: i64, y: i64) -> Option<T> {
+        let removed = self.cells.remove(&(x, y));
+        // only a cell on the edge can shrink the box
+        let on_edge = self
+            .bounds
+            .is_some_and(|b| x == b.min_x || x == b.max_x || y == b.min_y || y == b.max_y);
+        if removed.is_some() && on_edge {
+            self.bounds = None;
+            let keys: Vec<(i64, i64)> = self.cells.keys().copied().collect();
+            for (x, y) in keys {
+                self.touch(x, y);
+            }
+        }
+        removed
+    }
+
+    pub fn bounds(&self) -> Option<Bounds> {
+        self.bounds
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = ((i64, i64), &T)> {
+        self.cells.iter().map(|(pos, v)| (*pos, v))
+    }
+
+    /// One line per row of the bounding box; `f` gets `None` for empty cells.
+    pub fn to_text(&self, f: impl Fn(Option<&T>) -> char) -> String {
+        let Some(b) = self.bounds else {
+            return String::new();
+        };
+        let mut out = String::new();
+        for y in b.min_y..=b.max_y {
+            out.extend((b.min_x..=b.max_x).map(|x| f(self.get(x, y))));
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Palette indices covering the bounding box; `f` gets `None` for empty cells.
+    pub fn to_image(&self, f: impl Fn(Option<&T>) -> u8) -> Image {
+        let Some(b) = self.bounds else {
+            return Image::new(0, 0);
+        };
+        Image::from_fn(b.width(), b.height(), |x, y| {
+            f(self.get(b.min_x + x as i64, b.min_y + y as i64))
+        })
+    }
+}
+impl SparseGrid<usize> {
+    /// Bumps the counter for a cell, returning its new value.
+    pub fn increment(&mut self, x: i64, y: i64) -> usize {
+        let count = self.entry(x, y).or_insert(0);
+        *count += 1;
+        *count
+    }
+}
+impl<T> Default for SparseGrid<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Bounds, SparseGrid};
+
+    #[test]
+    fn grows_and_shrinks() {
+        let mut grid = SparseGrid::new();
+        assert_eq!(grid.bounds(), None);
+        grid.insert(0, 0, 'a');
+        grid.insert(-3, 2, 'b');
+        grid.insert(4, -1, 'c');
+        let expected = Bounds {
+            min_x: -3,
+            max_x: 4,
+            min_y: -1,
+            max_y: 2,
+        };
+        assert_eq!(grid.bounds(), Some(expected));
+        assert_eq!((expected.width(), expected.height()), (8, 4));
+        assert!(expected.contains(-3, -1) && !expected.contains(5, 0));
+        grid.remove(4, -1);
+        assert_eq!(grid.bounds().unwrap().max_x, 0);
+        assert_eq!(grid.bounds().unwrap().min_y, 0);
+    }
+
+    #[test]
+    fn counters_and_rendering() {
+        let mut grid = SparseGrid::new();
+        grid.increment(-1, 0);
+        grid.increment(1, 1);
+        assert_eq!(grid.increment(-1, 0), 2);
+        let text = grid.to_text(|c| match c {
+            None => '.',
+            Some(n) => char::from_digit(*n as u32, 10).unwrap(),
+        });
+        assert_eq!(text, "2..\n..1\n");
+        let image = grid.to_image(|c| c.map_or(0, |n| *n as u8));
+        assert_eq!((image.width(), image.height(), image.get(0, 0)), (3, 2, 2));
+    }
+}
@@ -0,0 +1,273 @@
+#![allow(dead_code)]
+
+use std::{
+    fmt::Write as _,
+    io::{self, Write},
+    thread::sleep,
+    time::{Duration, Instant},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+    Default,
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+    Rgb(u8, u8, u8),
+}
+impl Color {
+    fn sgr(&self, background: bool) -> String {
+        let base = if background { 40 } else { 30 };
+        match self {
+            Self::Default => (base + 9).to_string(),
+            Self::Black => base.to_string(),
+            Self::Red => (base + 1).to_string(),
+            Self::Green => (base + 2).to_string(),
+            Self::Yellow => (base + 3).to_string(),
+            Self::Blue => (base + 4).to_string(),
+            Self::Magenta => (base + 5).to_string(),
+            Self::Cyan => (base + 6).to_string(),
+            Self::White => (base + 7).to_string(),
+            Self::Rgb(r, g, b) => format!("{};2;{r};{g};{b}", base + 8),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Style {
+    pub fg: Color,
+    pub bg: Color,
+    pub bold: bool,
+}
+impl Style {
+    pub const PLAIN: Style = Style {
+        fg: Color::Default,
+        bg: Color::Default,
+        bold: false,
+    };
+
+    pub fn fg(fg: Color) -> Self {
+        Style { fg, ..Self::PLAIN }
+    }
+
+    pub fn on(self, bg: Color) -> Self {
+        Style { bg, ..self }
+    }
+
+    pub fn bold(self) -> Self {
+        Style { bold: true, ..self }
+    }
+
+    fn escape(&self) -> String {
+        let mut codes = vec!["0".to_string()];
+        if self.bold {
+            codes.push("1".to_string());
+        }
+        codes.push(self.fg.sgr(false));
+        codes.push(self.bg.sgr(true));
+        format!("\x1B[{}m", codes.join(";"))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cell {
+    pub ch: char,
+    pub style: Style,
+}
+impl Cell {
+    pub fn plain(ch: char) -> Self {
+        Cell {
+            ch,
+            style: Style::PLAIN,
+        }
+    }
+
+    pub fn styled(ch: char, style: Style) -> Self {
+        Cell { ch, style }
+    }
+}
+
+/// A grid of styled characters plus caption lines drawn above it.
+pub struct Frame {
+    cells: Vec<Cell>,
+    width: usize,
+    height: usize,
+    captions: Vec<String>,
+}
+impl Frame {
+    pub fn new(width: usize, height: usize) -> Self {
+        Frame {
+            cells: vec![Cell::plain(' '); width * height],
+            width,
+            height,
+            captions: Vec::new(),
+        }
+    }
+
+    pub fn from_fn(width: usize, height: usize, f: impl Fn(usize, usize) -> Cell) -> Self {
+        let cells = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| f(x, y))
+            .collect();
+        Frame {
+            cells,
+            width,
+            height,
+            captions: Vec::new(),
+        }
+    }
+
+    pub fn from_rows<T>(rows: &[Vec<T>], f: impl Fn(&T) -> Cell) -> Self {
+        let width = rows.first().map_or(0, |row| row.len());
+        Self::from_fn(width, rows.len(), |x, y| f(&rows[y][x]))
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Cell {
+        self.cells[(y * self.width) + x]
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, cell: Cell) {
+        self.cells[(y * self.width) + x] = cell;
+    }
+
+    /// Restyles the given positions, keeping their characters.
+    pub fn highlight(&mut self, positions: impl IntoIterator<Item = (usize, usize)>, style: Style) {
+        for (x, y) in positions {
+            self.cells[(y * self.width) + x].style = style;
+        }
+    }
+
+    /// Replaces the given positions with `cell`.
+    pub fn mark(&mut self, positions: impl IntoIterator<Item = (usize, usize)>, cell: Cell) {
+        for (x, y) in positions {
+            self.set(x, y, cell);
+        }
+    }
+
+    pub fn caption(&mut self, text: impl Into<String>) {
+        self.captions.push(text.into());
+    }
+
+    pub fn to_plain_string(&self) -> String {
+        let mut out = String::new();
+        for caption in &self.captions {
+            out.push_str(caption);
+            out.push('\n');
+        }
+        for row in self.cells.chunks(self.width.max(1)) {
+            out.extend(row.iter().map(|cell| cell.ch));
+            out.push('\n');
+        }
+        out
+    }
+
+    pub fn to_ansi_string(&self) -> String {
+        let mut out = String::new();
+        for caption in &self.captions {
+            // clear the rest of the line in case the last frame's caption was longer
+            writeln!(out, "{caption}\x1B[K").unwrap();
+        }
+        for row in self.cells.chunks(self.width.max(1)) {
+            let mut current = None;
+            for cell in row {
+                if current != Some(cell.style) {
+                    out.push_str(&cell.style.escape());
+                    current = Some(cell.style);
+                }
+                out.push(cell.ch);
+            }
+            out.push_str("\x1B[0m\x1B[K\n");
+        }
+        out
+    }
+
+    pub fn print(&self) {
+        print!("{}", self.to_ansi_string());
+    }
+}
+
+/// Draws frames in place at a fixed rate. Each frame is written in a single
+/// call over the previous one rather than clearing the screen, which avoids flicker.
+pub struct Animation {
+    frame_time: Duration,
+    last_frame: Option<Instant>,
+}
+impl Animation {
+    pub fn with_fps(fps: u32) -> Self {
+        assert!(fps > 0);
+        Animation {
+            frame_time: Duration::from_secs(1) / fps,
+            last_frame: None,
+        }
+    }
+
+    pub fn show(&mut self, frame: &Frame) {
+        let mut buffer = String::new();
+        match self.last_frame {
+            // clear once and hide the cursor
+            None => buffer.push_str("\x1B[2J\x1B[?25l"),
+            Some(last) => {
+                let elapsed = last.elapsed();
+                if elapsed < self.frame_time {
+                    sleep(self.frame_time - elapsed);
+                }
+            }
+        }
+        // move to the top left, draw, then clear anything below
+        buffer.push_str("\x1B[H");
+        buffer.push_str(&frame.to_ansi_string());
+        buffer.push_str("\x1B[J");
+
+        let mut stdout = io::stdout().lock();
+        stdout.write_all(buffer.as_bytes()).unwrap();
+        stdout.flush().unwrap();
+        self.last_frame = Some(Instant::now());
+    }
+}
+impl Drop for Animation {
+    fn drop(&mut self) {
+        if self.last_frame.is_some() {
+            print!("\x1B[?25h");
+            io::stdout().flush().unwrap();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Cell, Color, Frame, Style};
+
+    #[test]
+    fn plain_rendering() {
+        let rows = vec![b"#.#".to_vec(), b"..#".to_vec()];
+        let mut frame = Frame::from_rows(&rows, |b| Cell::plain(*b as char));
+        frame.caption("step 3");
+        frame.mark([(1, 1)], Cell::plain('O'));
+        assert_eq!(frame.to_plain_string(), "step 3\n#.#\n.O#\n");
+    }
+
+    #[test]
+    fn ansi_rendering() {
+        let mut frame = Frame::from_fn(3, 1, |_, _| Cell::plain('.'));
+        frame.highlight([(1, 0), (2, 0)], Style::fg(Color::Red).bold());
+        assert_eq!(
+            frame.to_ansi_string(),
+            "\x1B[0;39;49m.\x1B[0;1;31;49m..\x1B[0m\x1B[K\n"
+        );
+        let style = Style::fg(Color::Rgb(1, 2, 3)).on(Color::Blue);
+        assert_eq!(style.escape(), "\x1B[0;38;2;1;2;3;44m");
+    }
+}
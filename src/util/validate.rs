@@ -0,0 +1,110 @@
+#![allow(dead_code)]
+
+use std::{error::Error, fmt};
+
+/// Something a solver relies on being true of its input.
+pub struct Check<I: ?Sized> {
+    pub assumption: &'static str,
+    pub test: fn(&I) -> Result<(), String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    pub assumption: &'static str,
+    pub detail: String,
+}
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "input breaks assumption \"{}\": {}",
+            self.assumption, self.detail
+        )
+    }
+}
+impl Error for Violation {}
+
+/// Runs the checks in order and reports the first one that fails.
+pub fn validate<I: ?Sized>(input: &I, checks: &[Check<I>]) -> Result<(), Violation> {
+    for check in checks {
+        (check.test)(input).map_err(|detail| Violation {
+            assumption: check.assumption,
+            detail,
+        })?;
+    }
+    Ok(())
+}
+
+/// Fails with the first `(x, y)` of a rectangular grid whose cell isn't a wall.
+pub fn enclosed_by(rows: &[Vec<u8>], wall: u8) -> Result<(), String> {
+    let (width, height) = (rows.first().map_or(0, |row| row.len()), rows.len());
+    if let Some(y) = rows.iter().position(|row| row.len() != width) {
+        return Err(format!(
+            "row {y} is {} wide, row 0 is {width}",
+            rows[y].len()
+        ));
+    }
+    let edge = (0..height)
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .filter(|(x, y)| *x == 0 || *y == 0 || *x == width - 1 || *y == height - 1);
+    for (x, y) in edge {
+        if rows[y][x] != wall {
+            return Err(format!(
+                "({x}, {y}) on the edge is {:?}",
+                rows[y][x] as char
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{enclosed_by, validate, Check};
+
+    const CHECKS: &[Check<[u32]>] = &[
+        Check {
+            assumption: "not empty",
+            test: |v| {
+                if v.is_empty() {
+                    Err("no values".to_string())
+                } else {
+                    Ok(())
+                }
+            },
+        },
+        Check {
+            assumption: "sorted",
+            test: |v| match v.windows(2).position(|w| w[0] > w[1]) {
+                Some(i) => Err(format!("{} comes before {}", v[i], v[i + 1])),
+                None => Ok(()),
+            },
+        },
+    ];
+
+    #[test]
+    fn reports_first_violation() {
+        assert_eq!(validate(&[1, 2, 3][..], CHECKS), Ok(()));
+        let violation = validate(&[1, 3, 2][..], CHECKS).unwrap_err();
+        assert_eq!(violation.assumption, "sorted");
+        assert_eq!(
+            violation.to_string(),
+            "input breaks assumption \"sorted\": 3 comes before 2"
+        );
+        assert_eq!(
+            validate(&[][..], CHECKS).unwrap_err().assumption,
+            "not empty"
+        );
+    }
+
+    #[test]
+    fn walls() {
+        let rows: Vec<Vec<u8>> = ["###", "#.#", "###"].map(|r| r.bytes().collect()).to_vec();
+        assert_eq!(enclosed_by(&rows, b'#'), Ok(()));
+        let open: Vec<Vec<u8>> = ["###", "#..", "###"].map(|r| r.bytes().collect()).to_vec();
+        assert_eq!(
+            enclosed_by(&open, b'#').unwrap_err(),
+            "(2, 1) on the edge is '.'"
+        );
+    }
+}
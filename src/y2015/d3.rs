@@ -1,15 +1,17 @@
 #![allow(dead_code)]
 
-use std::{collections::HashSet, fs};
+use std::fs;
+
+use crate::util::sparse::SparseGrid;
 
 fn load_input(filename: &str) -> String {
     fs::read_to_string("input/2015/3/".to_owned() + filename).unwrap()
 }
 
-fn get_visited(directions: impl Iterator<Item = char>) -> HashSet<(i32, i32)> {
-    let mut visited = HashSet::new();
+// counts the presents delivered to each house
+fn deliver(directions: impl Iterator<Item = char>, houses: &mut SparseGrid<usize>) {
     let mut position = (0, 0);
-    visited.insert(position);
+    houses.increment(position.0, position.1);
     for c in directions {
         match c {
             '>' => position.0 += 1,
@@ -18,15 +20,15 @@ fn get_visited(directions: impl Iterator<Item = char>) -> HashSet<(i32, i32)> {
             '^' => position.1 -= 1,
             _ => unreachable!(),
         }
-        visited.insert(position);
+        houses.increment(position.0, position.1);
     }
-    visited
 }
 
 fn one_person(filename: &str) -> usize {
     let input = load_input(filename);
-    let visited = get_visited(input.trim().chars());
-    visited.len()
+    let mut houses = SparseGrid::new();
+    deliver(input.trim().chars(), &mut houses);
+    houses.len()
 }
 
 fn two_people(filename: &str) -> usize {
@@ -43,14 +45,15 @@ fn two_people(filename: &str) -> usize {
         .enumerate()
         .filter(|(idx, _)| idx % 2 == 1)
         .map(|(_, c)| c);
-    let set1 = get_visited(dir1);
-    let set2 = get_visited(dir2);
-    set1.union(&set2).count()
+    let mut houses = SparseGrid::new();
+    deliver(dir1, &mut houses);
+    deliver(dir2, &mut houses);
+    houses.len()
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{one_person, two_people};
+    use super::{deliver, one_person, two_people, SparseGrid};
 
     #[test]
     fn part1() {
@@ -58,6 +61,14 @@ mod tests {
         assert_eq!(result, 2592);
     }
 
+    #[test]
+    fn delivery_map() {
+        let mut houses = SparseGrid::new();
+        deliver("^>v<".chars(), &mut houses);
+        let text = houses.to_text(|c| c.map_or('.', |n| char::from_digit(*n as u32, 10).unwrap()));
+        assert_eq!(text, "11\n21\n");
+    }
+
     #[test]
     fn part2() {
         let result = two_people("input.txt");
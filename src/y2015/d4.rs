@@ -1,34 +1,77 @@
 #![allow(dead_code)]
 
-fn find_lowest_hash_with_five_zeroes(prefix: &str) -> Option<usize> {
-    for i in 0usize.. {
-        let input = format!("{prefix}{i}");
-        let digest = md5::compute(&input);
-        let digest_str = format!("{:x}", digest);
-        if digest_str.starts_with("00000") {
-            println!("{digest_str}");
-            return Some(i);
+use std::{io, path::Path};
+
+use crate::util::{
+    checkpoint::{Checkpoint, Progress},
+    parallel::Parallel,
+};
+
+// nonces checked between chances to save a checkpoint
+const BLOCK: usize = 1 << 20;
+
+fn hash_has_zeroes(prefix: &str, nonce: usize, target: &str) -> bool {
+    let digest = md5::compute(format!("{prefix}{nonce}"));
+    format!("{:x}", digest).starts_with(target)
+}
+
+fn find_lowest_hash_with_zeroes(prefix: &str, zeroes: usize) -> Option<usize> {
+    let target = "0".repeat(zeroes);
+    let nonce = Parallel::new().first_index(0, |i| hash_has_zeroes(prefix, i, &target));
+    Some(nonce)
+}
+
+// names the search in the checkpoint, so another prefix's progress isn't picked up
+fn search_id(prefix: &str, zeroes: usize) -> String {
+    format!("2015/4 {prefix} {zeroes}")
+}
+
+/// Saves the next unchecked nonce as it goes; `resume` starts from the last save.
+pub fn find_lowest_hash_resumable(
+    prefix: &str,
+    zeroes: usize,
+    checkpoint: &Path,
+    resume: bool,
+) -> io::Result<usize> {
+    let mut checkpoint = Checkpoint::new(checkpoint, search_id(prefix, zeroes));
+    let mut next = 0;
+    if resume {
+        if let Some(progress) = checkpoint.load()? {
+            next = progress.get("next").unwrap_or(0);
+        }
+    }
+    let target = "0".repeat(zeroes);
+    let pool = Parallel::new();
+    loop {
+        if let Some(nonce) =
+            pool.first_index_in(next..next + BLOCK, |i| hash_has_zeroes(prefix, i, &target))
+        {
+            checkpoint.finish()?;
+            return Ok(nonce);
         }
+        next += BLOCK;
+        checkpoint.save_every(|| Progress::new().set("next", next))?;
     }
-    None
+}
+
+fn find_lowest_hash_with_five_zeroes(prefix: &str) -> Option<usize> {
+    find_lowest_hash_with_zeroes(prefix, 5)
 }
 
 fn find_lowest_hash_with_six_zeroes(prefix: &str) -> Option<usize> {
-    for i in 0usize.. {
-        let input = format!("{prefix}{i}");
-        let digest = md5::compute(&input);
-        let digest_str = format!("{:x}", digest);
-        if digest_str.starts_with("000000") {
-            println!("{digest_str}");
-            return Some(i);
-        }
-    }
-    None
+    find_lowest_hash_with_zeroes(prefix, 6)
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{find_lowest_hash_with_five_zeroes, find_lowest_hash_with_six_zeroes};
+    use std::{env, process};
+
+    use crate::util::checkpoint::{Checkpoint, Progress};
+
+    use super::{
+        find_lowest_hash_resumable, find_lowest_hash_with_five_zeroes,
+        find_lowest_hash_with_six_zeroes, search_id,
+    };
 
     #[test]
     fn part1_example() {
@@ -50,4 +93,15 @@ mod tests {
         let result = find_lowest_hash_with_six_zeroes(input).unwrap();
         assert_eq!(9958218, result);
     }
+
+    #[test]
+    fn part2_resume() {
+        let path = env::temp_dir().join(format!("aoc_2015_d4_{}.txt", process::id()));
+        Checkpoint::new(&path, search_id("iwrupvqb", 6))
+            .save(&Progress::new().set("next", 9_500_000))
+            .unwrap();
+        let result = find_lowest_hash_resumable("iwrupvqb", 6, &path, true).unwrap();
+        assert_eq!(9958218, result);
+        assert!(!path.exists());
+    }
 }
@@ -1,6 +1,8 @@
 #![allow(dead_code)]
 use std::{collections::HashSet, fs::File, io::Read};
 
+use crate::util::bigint::Count;
+
 fn load_map(filename: &str) -> Vec<Vec<u8>> {
     let mut file = File::open("input/2024/10/".to_owned() + filename).unwrap();
     let mut contents = String::new();
@@ -11,15 +13,20 @@ fn load_map(filename: &str) -> Vec<Vec<u8>> {
         .collect()
 }
 
-fn look_for_summits(map: &Vec<Vec<u8>>, reachable: &mut Vec<Vec<u32>>, i: usize, j: usize) -> u32 {
-    if reachable[i][j] > 0 {
-        return reachable[i][j];
+fn look_for_summits<C: Count>(
+    map: &Vec<Vec<u8>>,
+    reachable: &mut Vec<Vec<C>>,
+    i: usize,
+    j: usize,
+) -> C {
+    if !reachable[i][j].is_zero() {
+        return reachable[i][j].clone();
     }
     let v = map[i][j];
     if v == 9 {
-        return 1;
+        return C::one();
     }
-    let mut count = 0;
+    let mut count = C::default();
     if i > 0 && map[i - 1][j] == v + 1 {
         count += look_for_summits(map, reachable, i - 1, j);
     }
@@ -32,16 +39,18 @@ fn look_for_summits(map: &Vec<Vec<u8>>, reachable: &mut Vec<Vec<u32>>, i: usize,
     if j > 0 && map[i][j - 1] == v + 1 {
         count += look_for_summits(map, reachable, i, j - 1)
     }
-    reachable[i][j] = count;
+    reachable[i][j] = count.clone();
     count
 }
 
-fn count_possible_paths(filename: &str) -> u32 {
+fn count_possible_paths<C: Count>(filename: &str) -> C {
     let map = load_map(filename);
-    let mut reachable_summits: Vec<Vec<u32>> =
-        map.iter().map(|line| vec![0u32; line.len()]).collect();
+    let mut reachable_summits: Vec<Vec<C>> = map
+        .iter()
+        .map(|line| vec![C::default(); line.len()])
+        .collect();
 
-    let mut count = 0;
+    let mut count = C::default();
     for i in 0..map.len() {
         for j in 0..map[i].len() {
             if map[i][j] == 0 {
@@ -111,13 +120,13 @@ mod tests {
 
     #[test]
     fn part2_example() {
-        let result = count_possible_paths("example.txt");
+        let result = count_possible_paths::<u32>("example.txt");
         assert_eq!(result, 81);
     }
 
     #[test]
     fn part2() {
-        let result = count_possible_paths("input.txt");
+        let result = count_possible_paths::<u32>("input.txt");
         assert_eq!(result, 1238);
     }
 }
@@ -1,6 +1,8 @@
 #![allow(dead_code)]
 
-use std::{collections::HashMap, fs::File, io::Read};
+use std::{fs::File, io::Read};
+
+use crate::util::{bigint::Count, math::digit_count, memo::Memo};
 
 fn load_input(filename: &str) -> Vec<usize> {
     let mut file = File::open("input/2024/11/".to_owned() + filename).unwrap();
@@ -12,51 +14,43 @@ fn load_input(filename: &str) -> Vec<usize> {
         .collect()
 }
 
-fn digit_count(mut v: usize) -> usize {
-    let mut count = 1;
-    while v >= 10 {
-        v /= 10;
-        count += 1;
-    }
-    count
-}
-
 // memoized key is (v, steps)
-fn count_after_applying_rules(
+fn count_after_applying_rules<C: Count>(
     v: usize,
     steps: usize,
-    memoized: &mut HashMap<(usize, usize), usize>,
-) -> usize {
+    memo: &mut Memo<(usize, usize), C>,
+) -> C {
     if steps == 0 {
-        return 1;
-    }
-    if let Some(count) = memoized.get(&(v, steps)) {
-        return *count;
+        return C::one();
     }
-    let count = if v == 0 {
-        count_after_applying_rules(1, steps - 1, memoized)
-    } else if digit_count(v) % 2 == 0 {
-        let divisor = 10usize.pow((digit_count(v) / 2).try_into().unwrap());
-        count_after_applying_rules(v / divisor, steps - 1, memoized)
-            + count_after_applying_rules(v % divisor, steps - 1, memoized)
-    } else {
-        count_after_applying_rules(v * 2024, steps - 1, memoized)
-    };
-    memoized.insert((v, steps), count);
-    count
+    memo.get_or_compute((v, steps), |memo| {
+        if v == 0 {
+            count_after_applying_rules(1, steps - 1, memo)
+        } else if digit_count(v).is_multiple_of(2) {
+            let divisor = 10usize.pow((digit_count(v) / 2).try_into().unwrap());
+            let mut count = count_after_applying_rules(v / divisor, steps - 1, memo);
+            count += count_after_applying_rules(v % divisor, steps - 1, memo);
+            count
+        } else {
+            count_after_applying_rules(v * 2024, steps - 1, memo)
+        }
+    })
 }
 
-fn count_nums_after_steps(input: Vec<usize>, steps: usize) -> usize {
-    let mut memoized = HashMap::new();
+fn count_nums_after_steps<C: Count>(input: Vec<usize>, steps: usize) -> C {
+    let mut memo = Memo::new();
     input
         .into_iter()
-        .map(|v| count_after_applying_rules(v, steps, &mut memoized))
+        .map(|v| count_after_applying_rules(v, steps, &mut memo))
         .sum()
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{count_nums_after_steps, digit_count, load_input};
+    use std::collections::HashMap;
+
+    use super::{count_nums_after_steps, load_input};
+    use crate::util::{bigint::BigUint, math::digit_count};
 
     fn apply_rules_to_vec(input: Vec<usize>, steps: usize) -> Vec<usize> {
         let mut new_vec = input;
@@ -67,7 +61,7 @@ mod tests {
             for num in last_vec {
                 if num == 0 {
                     new_vec.push(1);
-                } else if digit_count(num) % 2 == 0 {
+                } else if digit_count(num).is_multiple_of(2) {
                     let divisor = 10usize.pow((digit_count(num) / 2).try_into().unwrap());
                     new_vec.push(num / divisor);
                     new_vec.push(num % divisor);
@@ -83,24 +77,60 @@ mod tests {
     #[test]
     fn part1_example() {
         let input = load_input("example.txt");
-        let result = count_nums_after_steps(input, 25);
+        let result = count_nums_after_steps::<usize>(input, 25);
         assert_eq!(result, 55312);
     }
 
     #[test]
     fn part1() {
         let input = load_input("input.txt");
-        let result = count_nums_after_steps(input, 25);
+        let result = count_nums_after_steps::<usize>(input, 25);
         assert_eq!(result, 209412);
     }
 
     #[test]
     fn part2() {
         let input = load_input("input.txt");
-        let result = count_nums_after_steps(input, 75);
+        let result = count_nums_after_steps::<usize>(input, 75);
         assert_eq!(result, 248967696501656);
     }
 
+    // steps the whole multiset of stones forward, rather than recursing per stone
+    fn count_by_value(input: Vec<usize>, steps: usize) -> BigUint {
+        let mut counts: HashMap<usize, BigUint> = HashMap::new();
+        for v in input {
+            *counts.entry(v).or_default() += BigUint::from(1u64);
+        }
+        for _ in 0..steps {
+            let mut next: HashMap<usize, BigUint> = HashMap::new();
+            for (v, count) in counts {
+                let digits = digit_count(v);
+                let new = if v == 0 {
+                    vec![1]
+                } else if digits.is_multiple_of(2) {
+                    let divisor = 10usize.pow((digits / 2) as u32);
+                    vec![v / divisor, v % divisor]
+                } else {
+                    vec![v * 2024]
+                };
+                for n in new {
+                    *next.entry(n).or_default() += &count;
+                }
+            }
+            counts = next;
+        }
+        counts.into_values().sum()
+    }
+
+    #[test]
+    fn many_blinks_exact() {
+        // far past what a u128 can hold
+        let input = load_input("example.txt");
+        let exact: BigUint = count_nums_after_steps(input.clone(), 300);
+        assert!(exact > BigUint::from(u128::MAX));
+        assert_eq!(exact, count_by_value(input, 300));
+    }
+
     #[test]
     fn cycle_testing() {
         let input = vec![1];
@@ -3,6 +3,8 @@ use std::fs;
 
 use regex::Regex;
 
+use crate::util::math::{solve_2x2, solve_diophantine};
+
 #[derive(Eq, PartialEq, Hash, Debug, Clone)]
 struct Position {
     x: i64,
@@ -45,53 +47,56 @@ fn load_input(filename: &str) -> Vec<(Position, Position, Position)> {
     inputs
 }
 
-fn valid(a_diff: &Position, b_diff: &Position, prize: &Position, a: i64, b: i64) -> bool {
-    (a * a_diff.x) + (b * b_diff.x) == prize.x && (a * a_diff.y) + (b * b_diff.y) == prize.y
-}
-
-struct LinearEquation {
-    a: i64,
-    b: i64,
-    c: i64,
-}
-impl LinearEquation {
-    fn new(a: i64, b: i64, c: i64) -> Self {
-        LinearEquation { a, b, c }
+// when both buttons move along the same line, pick the cheapest non-negative
+// combination from the general solution of the x equation
+fn cheapest_collinear(a_diff: &Position, b_diff: &Position, prize: &Position) -> Option<i64> {
+    if a_diff.x * prize.y != a_diff.y * prize.x || b_diff.x * prize.y != b_diff.y * prize.x {
+        return None;
     }
-
-    fn multiply_by(&self, x: i64) -> Self {
-        LinearEquation {
-            a: self.a * x,
-            b: self.b * x,
-            c: self.c * x,
+    // both x steps are 0 when the line is vertical, so solve along y instead
+    let sol = if a_diff.x != 0 || b_diff.x != 0 {
+        solve_diophantine(a_diff.x, b_diff.x, prize.x)?
+    } else {
+        solve_diophantine(a_diff.y, b_diff.y, prize.y)?
+    };
+    // a = sol.x + k*dx and b = sol.y - k*dy, both written as base + k*step >= 0;
+    // a step of 0 (a button that doesn't move) leaves k free on that side
+    let (mut low, mut high) = (None, None);
+    for (base, step) in [(sol.x, sol.dx), (sol.y, -sol.dy)] {
+        match step.signum() {
+            0 if base < 0 => return None,
+            0 => (),
+            1 => low = low.max(Some(-base.div_euclid(step))),
+            _ => high = Some(high.unwrap_or(i64::MAX).min(base.div_euclid(-step))),
         }
     }
-
-    fn subtract_equation(&self, other: &LinearEquation) -> Self {
-        LinearEquation {
-            a: self.a - other.a,
-            b: self.b - other.b,
-            c: self.c - other.c,
+    if let (Some(low), Some(high)) = (low, high) {
+        if low > high {
+            return None;
         }
     }
+    // cost is linear in k and can't go negative, so one of the finite ends is cheapest
+    [low, high]
+        .into_iter()
+        .flatten()
+        .map(|k| {
+            let (a, b) = sol.at(k);
+            (a * 3) + b
+        })
+        .min()
 }
 
 fn path_to_prize_fast_way(a_diff: &Position, b_diff: &Position, prize: &Position) -> Option<i64> {
-    let x_eq = LinearEquation::new(a_diff.x, b_diff.x, prize.x);
-    let y_eq = LinearEquation::new(a_diff.y, b_diff.y, prize.y);
-
-    let x_by_ay = x_eq.multiply_by(y_eq.a);
-    let y_by_ax = y_eq.multiply_by(x_eq.a);
-
-    let b_only_eq = x_by_ay.subtract_equation(&y_by_ax);
-    let b = b_only_eq.c / b_only_eq.b;
-
-    let a = (x_eq.c - (x_eq.b * b)) / x_eq.a;
-    if valid(a_diff, b_diff, prize, a, b) {
-        Some((a * 3) + b)
-    } else {
-        None
+    let Some((a, b)) = solve_2x2((a_diff.x, b_diff.x, prize.x), (a_diff.y, b_diff.y, prize.y))
+    else {
+        return cheapest_collinear(a_diff, b_diff, prize);
+    };
+    let a: i64 = a.as_integer()?.try_into().ok()?;
+    let b: i64 = b.as_integer()?.try_into().ok()?;
+    if a < 0 || b < 0 {
+        return None;
     }
+    Some((a * 3) + b)
 }
 
 fn sum_cheapest_paths(filename: &str) -> i64 {
@@ -116,7 +121,7 @@ pub fn sum_cheapest_paths_part2(filename: &str) -> i64 {
 
 #[cfg(test)]
 mod tests {
-    use super::{sum_cheapest_paths, sum_cheapest_paths_part2};
+    use super::{path_to_prize_fast_way, sum_cheapest_paths, sum_cheapest_paths_part2, Position};
 
     #[test]
     fn part1_example() {
@@ -130,6 +135,45 @@ mod tests {
         assert_eq!(result, 33921);
     }
 
+    #[test]
+    fn collinear_buttons() {
+        let b = Position::new(1, 1);
+        // A costs 3 for 4 steps, so as many A presses as fit: 2 of A and 2 of B
+        let a = Position::new(4, 4);
+        let result = path_to_prize_fast_way(&a, &b, &Position::new(10, 10));
+        assert_eq!(result, Some(8));
+        // A costs 3 for 2 steps, so B all the way
+        let a = Position::new(2, 2);
+        let result = path_to_prize_fast_way(&a, &b, &Position::new(10, 10));
+        assert_eq!(result, Some(10));
+        let result = path_to_prize_fast_way(&a, &b, &Position::new(10, 11));
+        assert_eq!(result, None);
+    }
+
+    #[test]
+    fn negative_collinear_buttons() {
+        let a = Position::new(-4, -4);
+        let b = Position::new(-1, -1);
+        let result = path_to_prize_fast_way(&a, &b, &Position::new(-10, -10));
+        assert_eq!(result, Some(8));
+        // a button that doesn't move is never worth pressing
+        let still = Position::new(0, 0);
+        let result = path_to_prize_fast_way(&still, &b, &Position::new(-5, -5));
+        assert_eq!(result, Some(5));
+        let result = path_to_prize_fast_way(&b, &still, &Position::new(-5, -5));
+        assert_eq!(result, Some(15));
+    }
+
+    #[test]
+    fn vertical_collinear_buttons() {
+        let a = Position::new(0, 4);
+        let b = Position::new(0, 1);
+        let result = path_to_prize_fast_way(&a, &b, &Position::new(0, 10));
+        assert_eq!(result, Some(8));
+        let result = path_to_prize_fast_way(&a, &b, &Position::new(1, 10));
+        assert_eq!(result, None);
+    }
+
     #[test]
     fn part2_example() {
         let result = sum_cheapest_paths_part2("example.txt");
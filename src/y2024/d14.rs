@@ -1,9 +1,16 @@
 #![allow(dead_code)]
-use std::{fs, iter, thread::sleep, time::Duration};
+use std::{fs, io, ops::Range, path::Path, time::Duration};
 
-use itertools::Itertools;
 use regex::Regex;
 
+use crate::util::{
+    checkpoint::{Checkpoint, Progress},
+    image::{Export, Image, Rgb},
+    math::lcm,
+    term::{Animation, Cell, Color, Frame, Style},
+    validate::{validate, Check, Violation},
+};
+
 #[derive(Eq, PartialEq, Hash, Debug, Clone)]
 struct Position {
     x: i64,
@@ -55,9 +62,26 @@ fn positions_after_steps<'a>(
         })
 }
 
-fn part1_score(filename: &str, width: i64, height: i64, steps: i64) -> i64 {
-    assert!(width % 2 == 1);
-    assert!(height % 2 == 1);
+// quadrants leave out the middle row and column
+const FLOOR_CHECKS: &[Check<(i64, i64)>] = &[
+    Check {
+        assumption: "floor has a middle column",
+        test: |(width, _)| match width % 2 {
+            1 => Ok(()),
+            _ => Err(format!("width is {width}")),
+        },
+    },
+    Check {
+        assumption: "floor has a middle row",
+        test: |(_, height)| match height % 2 {
+            1 => Ok(()),
+            _ => Err(format!("height is {height}")),
+        },
+    },
+];
+
+fn part1_score(filename: &str, width: i64, height: i64, steps: i64) -> Result<i64, Violation> {
+    validate(&(width, height), FLOOR_CHECKS)?;
 
     let (starting_positions, velocities) = load_input(filename);
     let mut quandrant_counts = [0, 0, 0, 0];
@@ -72,32 +96,32 @@ fn part1_score(filename: &str, width: i64, height: i64, steps: i64) -> i64 {
             quandrant_counts[3] += 1;
         }
     }
-    quandrant_counts.iter().product()
+    Ok(quandrant_counts.iter().product())
 }
 
-fn render_grid(positions: &[Position], width: i64, height: i64, steps: i64) {
-    // clear screen
-    println!("======");
-    println!("| {steps} |");
-    println!("======");
-    let mut grid = vec![vec!['.'; width as usize]; height as usize];
-    for pos in positions {
-        grid[pos.y as usize][pos.x as usize] = 'X';
-    }
-    for (idx, row) in grid.into_iter().enumerate() {
-        let mut line: String = row
+fn render_grid(positions: &[Position], width: i64, height: i64, steps: i64) -> Frame {
+    // every other column is a spacer so the picture isn't squashed horizontally
+    let mut frame = Frame::from_fn(2 * width as usize, height as usize, |x, _| {
+        Cell::plain(if x % 2 == 0 { '.' } else { ' ' })
+    });
+    frame.mark(
+        positions
             .iter()
-            .interleave(iter::repeat(&' ').take(row.len()))
-            .collect();
-        if idx == 0 || idx == 2 {
-            line.push_str("======");
-        }
-        if idx == 1 {
-            line.push_str(&format!("| {steps} |"));
-        }
+            .map(|pos| (2 * pos.x as usize, pos.y as usize)),
+        Cell::styled('X', Style::fg(Color::Green).bold()),
+    );
+    frame.caption(format!("| {steps} |"));
+    frame
+}
 
-        println!("{line}");
+const ROBOT_PALETTE: [Rgb; 2] = [[16, 16, 32], [40, 200, 60]];
+
+fn robot_image(positions: &[Position], width: i64, height: i64) -> Image {
+    let mut image = Image::new(width as usize, height as usize);
+    for pos in positions {
+        image.set(pos.x as usize, pos.y as usize, 1);
     }
+    image
 }
 
 fn increment_positions(
@@ -113,20 +137,59 @@ fn increment_positions(
     }
 }
 
-fn clear_screen() {
-    print!("\x1B[2J");
-}
-
 fn in_center_up(pos: &Position, width: i64, height: i64) -> bool {
     pos.x >= width / 4 && pos.x <= width * 3 / 4 && pos.y < height / 2
 }
 
-fn part2_estimate(filename: &str, width: i64, height: i64) -> i64 {
-    let (mut positions, velocities) = load_input(filename);
+/// `None` if no tick in a full period looks like a tree.
+fn part2_estimate(filename: &str, width: i64, height: i64) -> Option<i64> {
+    let (positions, velocities) = load_input(filename);
+    first_tree_tick(positions, &velocities, width, height, 0, |_| Ok(())).unwrap()
+}
+
+fn search_id(filename: &str, width: i64, height: i64) -> String {
+    format!("2024/14 {filename} {width}x{height}")
+}
+
+/// `part2_estimate` that saves its tick to `checkpoint` and can resume from it.
+pub fn part2_estimate_resumable(
+    filename: &str,
+    width: i64,
+    height: i64,
+    checkpoint: &Path,
+    resume: bool,
+) -> io::Result<Option<i64>> {
+    let mut checkpoint = Checkpoint::new(checkpoint, search_id(filename, width, height));
+    let mut start = 0;
+    if resume {
+        if let Some(progress) = checkpoint.load()? {
+            start = progress.get("tick").unwrap_or(0);
+        }
+    }
+    let (positions, velocities) = load_input(filename);
+    // robots move in straight lines, so the tick is all there is to save
+    let positions = positions_after_steps(&positions, &velocities, width, height, start).collect();
+    let tick = first_tree_tick(positions, &velocities, width, height, start, |tick| {
+        checkpoint.save_every(|| Progress::new().set("tick", tick))
+    })?;
+    checkpoint.finish()?;
+    Ok(tick)
+}
+
+// `positions` are the robots after `ticks` steps; `each_tick` is called after every step.
+// Ok(None) if a whole period passes without a tree.
+fn first_tree_tick(
+    mut positions: Vec<Position>,
+    velocities: &[Position],
+    width: i64,
+    height: i64,
+    mut ticks: i64,
+    mut each_tick: impl FnMut(i64) -> io::Result<()>,
+) -> io::Result<Option<i64>> {
     let threshold = positions.len() / 2;
-    let mut ticks = 0;
-    while ticks < 1000000000 {
-        increment_positions(&mut positions, &velocities, width, height);
+    // the robots' positions repeat once the x and y periods line up
+    while ticks < lcm(width, height) {
+        increment_positions(&mut positions, velocities, width, height);
         ticks += 1;
         if positions
             .iter()
@@ -134,17 +197,18 @@ fn part2_estimate(filename: &str, width: i64, height: i64) -> i64 {
             .count()
             >= threshold
         {
-            break;
+            return Ok(Some(ticks));
         }
+        each_tick(ticks)?;
     }
-    ticks
+    Ok(None)
 }
 
 pub fn part2_display(filename: &str, width: i64, height: i64, tick: i64) {
     let (positions, velocities) = load_input(filename);
     let positions: Vec<Position> =
         positions_after_steps(&positions, &velocities, width, height, tick).collect();
-    render_grid(&positions, width, height, tick);
+    render_grid(&positions, width, height, tick).print();
 }
 
 pub fn part2_animate(filename: &str, width: i64, height: i64, start_at: i64) {
@@ -152,34 +216,157 @@ pub fn part2_animate(filename: &str, width: i64, height: i64, start_at: i64) {
     let mut positions: Vec<Position> =
         positions_after_steps(&positions, &velocities, width, height, start_at).collect();
     let mut steps = start_at;
+    let mut animation = Animation::with_fps(1);
     loop {
-        clear_screen();
-        render_grid(&positions, width, height, steps);
+        animation.show(&render_grid(&positions, width, height, steps));
         increment_positions(&mut positions, &velocities, width, height);
         steps += 1;
-        sleep(Duration::from_millis(1000));
     }
 }
 
+pub fn part2_export(
+    filename: &str,
+    width: i64,
+    height: i64,
+    tick: i64,
+    path: &str,
+) -> io::Result<()> {
+    let (positions, velocities) = load_input(filename);
+    let positions: Vec<Position> =
+        positions_after_steps(&positions, &velocities, width, height, tick).collect();
+    Export::new(&ROBOT_PALETTE)
+        .scale(4)
+        .save(&robot_image(&positions, width, height), path)
+}
+
+pub fn part2_export_gif(
+    filename: &str,
+    width: i64,
+    height: i64,
+    ticks: Range<i64>,
+    path: &str,
+) -> io::Result<()> {
+    let (positions, velocities) = load_input(filename);
+    let frames = ticks.map(|tick| {
+        let positions: Vec<Position> =
+            positions_after_steps(&positions, &velocities, width, height, tick).collect();
+        robot_image(&positions, width, height)
+    });
+    Export::new(&ROBOT_PALETTE)
+        .scale(2)
+        .save_gif(frames, Duration::from_millis(200), path)
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{part1_score, part2_estimate};
+    use std::{env, fs, io, process};
+
+    use crate::util::{
+        checkpoint::{Checkpoint, Progress},
+        snapshot::assert_snapshot,
+    };
+
+    use super::{
+        first_tree_tick, load_input, part1_score, part2_estimate, part2_estimate_resumable,
+        part2_export, part2_export_gif, positions_after_steps, render_grid, search_id, Position,
+    };
+
+    fn snapshot_frame(filename: &str, width: i64, height: i64, steps: i64) -> String {
+        let (positions, velocities) = load_input(filename);
+        let positions: Vec<Position> =
+            positions_after_steps(&positions, &velocities, width, height, steps).collect();
+        render_grid(&positions, width, height, steps).to_plain_string()
+    }
 
     #[test]
     fn part1_example() {
-        let result = part1_score("example.txt", 11, 7, 100);
+        let result = part1_score("example.txt", 11, 7, 100).unwrap();
         assert_eq!(result, 12);
     }
 
     #[test]
     fn part1() {
-        let result = part1_score("input.txt", 101, 103, 100);
+        let result = part1_score("input.txt", 101, 103, 100).unwrap();
         assert_eq!(result, 215987200);
     }
 
     #[test]
     fn part2() {
         let result = part2_estimate("input.txt", 101, 103);
-        assert_eq!(result, 8050);
+        assert_eq!(result, Some(8050));
+    }
+
+    #[test]
+    fn part2_no_tree() {
+        // parked outside the middle, so a full period passes without a tree
+        let positions = vec![Position::new(0, 0), Position::new(0, 6)];
+        let velocities = vec![Position::new(0, 0); 2];
+        let result = first_tree_tick(positions, &velocities, 11, 7, 0, |_| Ok(()));
+        assert_eq!(result.unwrap(), None);
+    }
+
+    #[test]
+    fn part2_export_png() {
+        let path = env::temp_dir().join(format!("aoc_2024_d14_{}.png", process::id()));
+        part2_export("input.txt", 101, 103, 8050, path.to_str().unwrap()).unwrap();
+        let bytes = fs::read(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(&bytes[..8], b"\x89PNG\r\n\x1a\n");
+        // width and height at scale 4
+        assert_eq!(&bytes[16..24], &[0, 0, 1, 148, 0, 0, 1, 156]);
+    }
+
+    #[test]
+    fn part2_export_gif_frames() {
+        let path = env::temp_dir().join(format!("aoc_2024_d14_{}.gif", process::id()));
+        part2_export_gif("example.txt", 11, 7, 0..4, path.to_str().unwrap()).unwrap();
+        let bytes = fs::read(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(&bytes[..6], b"GIF89a");
+        // width and height at scale 2
+        assert_eq!(&bytes[6..10], &[22, 0, 14, 0]);
+        // every frame starts with a graphics control block
+        let frames = bytes
+            .windows(4)
+            .filter(|w| w == b"\x21\xF9\x04\x00")
+            .count();
+        assert_eq!(frames, 4);
+        assert_eq!(bytes.last(), Some(&0x3B));
+    }
+
+    #[test]
+    fn part1_example_frame() {
+        let frame = snapshot_frame("example.txt", 11, 7, 100);
+        assert_snapshot("2024/14/part1_example", &frame);
+    }
+
+    #[test]
+    fn part2_tree_frame() {
+        let frame = snapshot_frame("input.txt", 101, 103, 8050);
+        assert_snapshot("2024/14/part2_tree", &frame);
+    }
+
+    #[test]
+    fn part1_even_floor() {
+        let violation = part1_score("example.txt", 11, 8, 100).unwrap_err();
+        assert_eq!(violation.assumption, "floor has a middle row");
+    }
+
+    #[test]
+    fn part2_resume() {
+        let path = env::temp_dir().join(format!("aoc_2024_d14_{}.txt", process::id()));
+        // left behind by the example's floor, so not ours to resume
+        Checkpoint::new(&path, search_id("example.txt", 11, 7))
+            .save(&Progress::new().set("tick", 50))
+            .unwrap();
+        let err = part2_estimate_resumable("input.txt", 101, 103, &path, true).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        Checkpoint::new(&path, search_id("input.txt", 101, 103))
+            .save(&Progress::new().set("tick", 8049))
+            .unwrap();
+        let result = part2_estimate_resumable("input.txt", 101, 103, &path, true).unwrap();
+        assert_eq!(result, Some(8050));
+        assert!(!path.exists());
     }
 }
@@ -1,5 +1,10 @@
 #![allow(dead_code)]
-use std::{char, collections::VecDeque, fs};
+use std::{collections::VecDeque, fs, io};
+
+use crate::util::{
+    image::{Export, Image},
+    term::{Cell, Color, Frame, Style},
+};
 
 struct TwoDArray<T: Clone> {
     arr: Vec<T>,
@@ -431,24 +436,45 @@ fn process_map_wide(map: &mut Map, directions: &[Direction]) {
     }
 }
 
+fn render_map(map: &Map) -> Frame {
+    let mut frame = Frame::from_fn(map.map.width, map.map.height, |x, y| {
+        match map.map.get(x, y) {
+            Space::Wall => Cell::plain('#'),
+            Space::Box => Cell::styled('O', Style::fg(Color::Yellow)),
+            Space::Free => Cell::plain('.'),
+            Space::Robot => Cell::plain('@'),
+            Space::BoxLeft => Cell::styled('[', Style::fg(Color::Yellow)),
+            Space::BoxRight => Cell::styled(']', Style::fg(Color::Yellow)),
+        }
+    });
+    frame.set(
+        map.robot_pos.0,
+        map.robot_pos.1,
+        Cell::styled('@', Style::fg(Color::Red).bold()),
+    );
+    frame
+}
+
 fn display_map(map: &Map) {
-    for h in 0..map.map.height {
-        let tmp_vec: Vec<char> = map.map.arr[h * map.map.width..(h + 1) * map.map.width]
-            .iter()
-            .map(|c| match c {
-                Space::Wall => '#',
-                Space::Box => 'O',
-                Space::Free => '.',
-                Space::Robot => '@',
-                Space::BoxLeft => '[',
-                Space::BoxRight => ']',
-            })
-            .collect();
-        println!("{:?}", tmp_vec);
-    }
+    render_map(map).print();
     println!();
 }
 
+fn export_map(map: &Map, path: &str) -> io::Result<()> {
+    let mut image = Image::from_fn(map.map.width, map.map.height, |x, y| {
+        match map.map.get(x, y) {
+            Space::Free => 0,
+            Space::Wall => 1,
+            Space::Box | Space::BoxLeft | Space::BoxRight => 2,
+            Space::Robot => 3,
+        }
+    });
+    image.set(map.robot_pos.0, map.robot_pos.1, 3);
+    Export::new(&[[20, 20, 20], [110, 110, 120], [190, 130, 50], [230, 40, 40]])
+        .scale(8)
+        .save(&image, path)
+}
+
 fn sum_gps_of_boxes_after_processing(filename: &str) -> usize {
     let (mut map, directions) = get_input(filename);
     process_map(&mut map, &directions);
@@ -503,7 +529,14 @@ fn wider_sum_gps_of_boxes_after_processing(filename: &str) -> usize {
 
 #[cfg(test)]
 mod tests {
-    use super::{sum_gps_of_boxes_after_processing, wider_sum_gps_of_boxes_after_processing};
+    use std::{env, fs, process};
+
+    use crate::util::snapshot::assert_snapshot;
+
+    use super::{
+        export_map, get_input, process_map, process_map_wide, render_map,
+        sum_gps_of_boxes_after_processing, widen_map, wider_sum_gps_of_boxes_after_processing,
+    };
 
     #[test]
     fn part1_small_example() {
@@ -534,4 +567,39 @@ mod tests {
         let result = wider_sum_gps_of_boxes_after_processing("input.txt");
         assert_eq!(result, 1468005);
     }
+
+    #[test]
+    fn part1_small_example_export() {
+        let (map, _) = get_input("small_example.txt");
+        let path = env::temp_dir().join(format!("aoc_2024_d15_{}.ppm", process::id()));
+        export_map(&map, path.to_str().unwrap()).unwrap();
+        let bytes = fs::read(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        // 8x8 at scale 8, starting in the wall's colour
+        let header = b"P6\n64 64\n255\n";
+        assert_eq!(&bytes[..header.len()], header);
+        assert_eq!(bytes.len(), header.len() + (64 * 64 * 3));
+        assert_eq!(&bytes[header.len()..header.len() + 3], &[110, 110, 120]);
+    }
+
+    #[test]
+    fn part1_large_example_map() {
+        let (mut map, directions) = get_input("large_example.txt");
+        process_map(&mut map, &directions);
+        assert_snapshot(
+            "2024/15/part1_large_example",
+            &render_map(&map).to_plain_string(),
+        );
+    }
+
+    #[test]
+    fn part2_large_example_map() {
+        let (mut map, directions) = get_input("large_example.txt");
+        widen_map(&mut map);
+        process_map_wide(&mut map, &directions);
+        assert_snapshot(
+            "2024/15/part2_large_example",
+            &render_map(&map).to_plain_string(),
+        );
+    }
 }
@@ -1,15 +1,50 @@
 #![allow(dead_code)]
 use std::{
     collections::{BinaryHeap, HashSet},
-    fs,
+    fs, io,
 };
 
-fn get_maze(filename: &str) -> Vec<Vec<u8>> {
+use crate::util::{
+    image::{Export, Image},
+    term::{Cell, Color, Frame, Style},
+    validate::{enclosed_by, validate, Check, Violation},
+};
+
+const MAZE_CHECKS: &[Check<[Vec<u8>]>] = &[
+    // moves never bounds check, they stop at walls
+    Check {
+        assumption: "maze is a rectangle surrounded by walls",
+        test: |maze| enclosed_by(maze, b'#'),
+    },
+    Check {
+        assumption: "start is in the bottom left corner",
+        test: |maze| {
+            if maze.len() < 3 || maze[maze.len() - 2].len() < 2 {
+                return Err(format!("maze is only {} rows high", maze.len()));
+            }
+            match maze[maze.len() - 2][1] {
+                b'S' => Ok(()),
+                other => Err(format!("(1, {}) is {:?}", maze.len() - 2, other as char)),
+            }
+        },
+    },
+    Check {
+        assumption: "maze has one end",
+        test: |maze| match maze.iter().flatten().filter(|b| **b == b'E').count() {
+            1 => Ok(()),
+            n => Err(format!("found {n}")),
+        },
+    },
+];
+
+fn get_maze(filename: &str) -> Result<Vec<Vec<u8>>, Violation> {
     let contents = fs::read_to_string("input/2024/16/".to_owned() + filename).unwrap();
-    contents
+    let maze: Vec<Vec<u8>> = contents
         .lines()
         .map(|line| line.bytes().collect())
-        .collect()
+        .collect();
+    validate(&maze[..], MAZE_CHECKS)?;
+    Ok(maze)
 }
 
 #[derive(Eq, PartialEq, Clone)]
@@ -119,10 +154,10 @@ fn find_shortest_path(maze: &[Vec<u8>], pos: (i64, i64)) -> usize {
     unreachable!();
 }
 
-pub fn best_maze_score(filename: &str) -> usize {
-    let maze = get_maze(filename);
+pub fn best_maze_score(filename: &str) -> Result<usize, Violation> {
+    let maze = get_maze(filename)?;
     let starting_pos = (1i64, (maze.len() - 2) as i64);
-    find_shortest_path(&maze, starting_pos)
+    Ok(find_shortest_path(&maze, starting_pos))
 }
 
 fn build_best_paths(
@@ -199,63 +234,129 @@ fn build_best_paths(
     best_tiles
 }
 
-fn show_best_tiles(maze: &mut [Vec<u8>], best_tiles: impl Iterator<Item = (i64, i64)>) {
+fn render_best_tiles(maze: &[Vec<u8>], best_tiles: impl Iterator<Item = (i64, i64)>) -> Frame {
+    let mut frame = Frame::from_rows(maze, |b| Cell::plain(*b as char));
+    frame.mark(
+        best_tiles.map(|(x, y)| (x as usize, y as usize)),
+        Cell::styled('O', Style::fg(Color::Green).bold()),
+    );
+    frame
+}
+
+fn show_best_tiles(maze: &[Vec<u8>], best_tiles: impl Iterator<Item = (i64, i64)>) {
+    render_best_tiles(maze, best_tiles).print();
+}
+
+fn export_best_tiles(
+    maze: &[Vec<u8>],
+    best_tiles: impl Iterator<Item = (i64, i64)>,
+    path: &str,
+) -> io::Result<()> {
+    let mut image = Image::from_fn(maze[0].len(), maze.len(), |x, y| {
+        if maze[y][x] == b'#' {
+            1
+        } else {
+            0
+        }
+    });
     for (x, y) in best_tiles {
-        maze[y as usize][x as usize] = b'O';
-    }
-    for line in maze {
-        let s: String = line
-            .iter()
-            .map(|c| char::from_u32(*c as u32).unwrap())
-            .collect();
-        println!("{s}");
+        image.set(x as usize, y as usize, 2);
     }
+    Export::new(&[[20, 20, 20], [110, 110, 120], [40, 200, 60]])
+        .scale(6)
+        .save(&image, path)
 }
 
-fn count_tiles_on_best_paths(filename: &str) -> usize {
-    let maze = get_maze(filename);
+fn count_tiles_on_best_paths(filename: &str) -> Result<usize, Violation> {
+    let maze = get_maze(filename)?;
     let starting_pos = (1, (maze.len() - 2) as i64);
     let best_tiles = build_best_paths(&maze, starting_pos, find_shortest_path(&maze, starting_pos));
-    best_tiles.len()
+    Ok(best_tiles.len())
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{best_maze_score, count_tiles_on_best_paths};
+    use std::{env, fs, process};
+
+    use crate::util::{snapshot::assert_snapshot, validate::validate};
+
+    use super::{
+        best_maze_score, build_best_paths, count_tiles_on_best_paths, export_best_tiles,
+        find_shortest_path, get_maze, render_best_tiles, MAZE_CHECKS,
+    };
 
     #[test]
     fn part1_small_example() {
-        let result = best_maze_score("small_example.txt");
+        let result = best_maze_score("small_example.txt").unwrap();
         assert_eq!(result, 7036);
     }
 
     #[test]
     fn part1_example() {
-        let result = best_maze_score("example.txt");
+        let result = best_maze_score("example.txt").unwrap();
         assert_eq!(result, 11048);
     }
 
     #[test]
     fn part1() {
-        let result = best_maze_score("input.txt");
+        let result = best_maze_score("input.txt").unwrap();
         assert_eq!(result, 109516);
     }
 
     #[test]
     fn part2_small_example() {
-        let result = count_tiles_on_best_paths("small_example.txt");
+        let result = count_tiles_on_best_paths("small_example.txt").unwrap();
         assert_eq!(result, 45);
     }
 
     #[test]
     fn part2_example() {
-        let result = count_tiles_on_best_paths("example.txt");
+        let result = count_tiles_on_best_paths("example.txt").unwrap();
         assert_eq!(result, 64);
     }
 
     #[test]
     fn part2() {
-        let result = count_tiles_on_best_paths("input.txt");
+        let result = count_tiles_on_best_paths("input.txt").unwrap();
         assert_eq!(result, 568);
     }
+
+    #[test]
+    fn part2_example_tiles() {
+        let maze = get_maze("example.txt").unwrap();
+        let start = (1, (maze.len() - 2) as i64);
+        let best_tiles = build_best_paths(&maze, start, find_shortest_path(&maze, start));
+        let frame = render_best_tiles(&maze, best_tiles.into_iter());
+        assert_snapshot("2024/16/part2_example", &frame.to_plain_string());
+    }
+
+    #[test]
+    fn part2_example_export() {
+        let maze = get_maze("example.txt").unwrap();
+        let start = (1, (maze.len() - 2) as i64);
+        let best_tiles = build_best_paths(&maze, start, find_shortest_path(&maze, start));
+        let path = env::temp_dir().join(format!("aoc_2024_d16_{}.png", process::id()));
+        export_best_tiles(&maze, best_tiles.into_iter(), path.to_str().unwrap()).unwrap();
+        let bytes = fs::read(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(&bytes[..8], b"\x89PNG\r\n\x1a\n");
+        // 17x17 at scale 6
+        assert_eq!(&bytes[16..24], &[0, 0, 0, 102, 0, 0, 0, 102]);
+        assert_eq!(&bytes[bytes.len() - 8..bytes.len() - 4], b"IEND");
+    }
+
+    #[test]
+    fn rejects_moved_start() {
+        let maze: Vec<Vec<u8>> = ["#####", "#..E#", "#.S.#", "#####"]
+            .map(|r| r.bytes().collect())
+            .to_vec();
+        let violation = validate(&maze[..], MAZE_CHECKS).unwrap_err();
+        assert_eq!(violation.assumption, "start is in the bottom left corner");
+        assert_eq!(violation.detail, "(1, 2) is '.'");
+        for rows in [&[][..], &["###"][..]] {
+            let maze: Vec<Vec<u8>> = rows.iter().map(|r| r.bytes().collect()).collect();
+            let violation = validate(&maze[..], MAZE_CHECKS).unwrap_err();
+            assert_eq!(violation.assumption, "start is in the bottom left corner");
+        }
+    }
 }
@@ -4,6 +4,8 @@ use std::fs;
 
 use itertools::Itertools;
 
+use crate::util::search::{Search, SearchState, Strategy};
+
 struct Computer {
     reg_a: usize,
     reg_b: usize,
@@ -119,22 +121,49 @@ fn computer_output(filename: &str) -> String {
     output.iter().join(",")
 }
 
-fn three_bits_backtrack(program: &[u8], prefix: usize, len: usize) -> Option<usize> {
-    let idx = program.len() - len;
-    let mut computer = Computer::with_program(program);
-    for i in 0..8 {
-        let a = (prefix << 3) + i;
-        computer.reg_a = a;
-        computer.ptr = 0;
-        if computer.run() == program[idx..] {
-            if len == program.len() {
-                return Some(a);
-            } else if let Some(res) = three_bits_backtrack(program, a, len + 1) {
-                return Some(res);
-            }
+/// Register A values that make the program print its last `len` instructions.
+/// Each output digit depends on three more bits of A, so extend three bits at a time.
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct QuineSuffix<'a> {
+    program: &'a [u8],
+    a: usize,
+    len: usize,
+}
+impl SearchState for QuineSuffix<'_> {
+    fn is_goal(&self) -> bool {
+        self.len == self.program.len()
+    }
+
+    fn successors(&self, out: &mut Vec<Self>) {
+        if self.len < self.program.len() {
+            out.extend((0..8).map(|i| QuineSuffix {
+                a: (self.a << 3) + i,
+                len: self.len + 1,
+                ..*self
+            }));
         }
     }
-    None
+
+    fn prune(&self) -> bool {
+        if self.len == 0 {
+            return false;
+        }
+        let mut computer = Computer::with_program(self.program);
+        computer.reg_a = self.a;
+        computer.run() != self.program[self.program.len() - self.len..]
+    }
+}
+
+fn three_bits_backtrack(program: &[u8]) -> Option<usize> {
+    let root = QuineSuffix {
+        program,
+        a: 0,
+        len: 0,
+    };
+    Search::new(Strategy::DepthFirst)
+        .run(root)
+        .solution
+        .map(|s| s.a)
 }
 
 #[cfg(test)]
@@ -156,14 +185,14 @@ mod tests {
     #[test]
     fn part2_example() {
         let computer = load_computer("example_part2.txt");
-        let result = three_bits_backtrack(&computer.program, 0, 1).unwrap();
+        let result = three_bits_backtrack(&computer.program).unwrap();
         assert_eq!(result, 117440);
     }
 
     #[test]
     fn part2() {
         let computer = load_computer("input.txt");
-        let result = three_bits_backtrack(&computer.program, 0, 1).unwrap();
+        let result = three_bits_backtrack(&computer.program).unwrap();
         assert_eq!(result, 164542125272765);
     }
 }
@@ -1,15 +1,20 @@
 #![allow(dead_code)]
 
-use std::{collections::BinaryHeap, fs};
+use std::{collections::BinaryHeap, fs, io};
+
+use crate::util::{
+    bitgrid::BitGrid,
+    image::{Export, Image},
+};
 
 #[derive(Eq, PartialEq)]
 struct Position {
     x: usize,
     y: usize,
-    steps: u16,
+    steps: usize,
 }
 impl Position {
-    fn new(x: usize, y: usize, steps: u16) -> Self {
+    fn new(x: usize, y: usize, steps: usize) -> Self {
         Position { x, y, steps }
     }
 }
@@ -25,29 +30,36 @@ impl PartialOrd for Position {
 }
 
 struct Maze {
-    maze: Vec<u128>,
+    maze: BitGrid,
     width: usize,
     height: usize,
 }
 impl Maze {
     fn new(width: usize, height: usize) -> Self {
         Maze {
-            maze: vec![0u128; height],
+            maze: BitGrid::new(width, height),
             width,
             height,
         }
     }
 
     fn set_pos(&mut self, x: usize, y: usize) {
-        self.maze[y] |= 1 << x;
+        self.maze.set(x, y);
     }
 
     fn get_pos(&self, x: usize, y: usize) -> bool {
-        self.maze[y] & (1 << x) > 0
+        self.maze.get(x, y)
     }
 }
 
-fn find_shortest_path(maze: &Maze, seen: &mut [Vec<u16>]) -> Option<u16> {
+fn export_maze(maze: &Maze, path: &str) -> io::Result<()> {
+    let image = Image::from_fn(maze.width, maze.height, |x, y| maze.get_pos(x, y) as u8);
+    Export::new(&[[20, 20, 20], [200, 60, 60]])
+        .scale(8)
+        .save(&image, path)
+}
+
+fn find_shortest_path(maze: &Maze, seen: &mut [Vec<usize>]) -> Option<usize> {
     let mut queue = BinaryHeap::new();
     let start_pos = Position::new(0, 0, 0);
     let dest_x = maze.width - 1;
@@ -114,10 +126,10 @@ fn shortest_path_through_corrupted_memory(
     map_width: usize,
     map_height: usize,
     ticks: usize,
-) -> u16 {
+) -> usize {
     let positions = load_input(filename);
     let maze = build_maze(&positions[..ticks], map_width, map_height);
-    let mut seen = vec![vec![u16::MAX; map_width]; map_height];
+    let mut seen = vec![vec![usize::MAX; map_width]; map_height];
     find_shortest_path(&maze, &mut seen).unwrap()
 }
 
@@ -128,7 +140,7 @@ fn first_coord_to_block_exit(
     skip_ticks: usize,
 ) -> (usize, usize) {
     let positions = load_input(filename);
-    let mut seen = vec![vec![u16::MAX; map_width]; map_height];
+    let mut seen = vec![vec![usize::MAX; map_width]; map_height];
     let mut low = skip_ticks;
     let mut high = positions.len() - 1;
     let mut mid = (low + high) / 2;
@@ -137,7 +149,7 @@ fn first_coord_to_block_exit(
         let maze = build_maze(&positions[..=mid], map_width, map_height);
         seen.iter_mut().for_each(|row| {
             row.iter_mut().for_each(|p| {
-                *p = u16::MAX;
+                *p = usize::MAX;
             })
         });
         if find_shortest_path(&maze, &mut seen).is_none() {
@@ -152,7 +164,12 @@ fn first_coord_to_block_exit(
 
 #[cfg(test)]
 mod tests {
-    use super::{first_coord_to_block_exit, shortest_path_through_corrupted_memory};
+    use std::{env, fs, process};
+
+    use super::{
+        build_maze, export_maze, find_shortest_path, first_coord_to_block_exit, load_input,
+        shortest_path_through_corrupted_memory,
+    };
 
     #[test]
     fn part1_example() {
@@ -160,6 +177,20 @@ mod tests {
         assert_eq!(result, 22);
     }
 
+    #[test]
+    fn part1_example_export() {
+        let positions = load_input("example.txt");
+        let maze = build_maze(&positions[..12], 7, 7);
+        let path = env::temp_dir().join(format!("aoc_2024_d18_{}.png", process::id()));
+        export_maze(&maze, path.to_str().unwrap()).unwrap();
+        let bytes = fs::read(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(&bytes[..8], b"\x89PNG\r\n\x1a\n");
+        // 7x7 at scale 8
+        assert_eq!(&bytes[16..24], &[0, 0, 0, 56, 0, 0, 0, 56]);
+        assert_eq!(&bytes[bytes.len() - 8..bytes.len() - 4], b"IEND");
+    }
+
     #[test]
     fn part1() {
         let result = shortest_path_through_corrupted_memory("input.txt", 71, 71, 1024);
@@ -177,4 +208,32 @@ mod tests {
         let result = first_coord_to_block_exit("input.txt", 71, 71, 1024);
         assert_eq!(result, (36, 17));
     }
+
+    #[test]
+    fn wider_than_128() {
+        // a wall down column 150 with a single gap at the bottom
+        let walls: Vec<(usize, usize)> = (0..4).map(|y| (150, y)).collect();
+        let maze = build_maze(&walls, 300, 5);
+        let mut seen = vec![vec![usize::MAX; 300]; 5];
+        assert_eq!(find_shortest_path(&maze, &mut seen), Some(303));
+    }
+
+    #[test]
+    fn path_longer_than_u16() {
+        // walls on every odd row, gaps at alternating ends, so the path
+        // snakes across all 221 open rows
+        let (width, height) = (300, 441);
+        let walls: Vec<(usize, usize)> = (1..height)
+            .step_by(2)
+            .flat_map(|y| {
+                let gap = if y % 4 == 1 { width - 1 } else { 0 };
+                (0..width).filter(move |x| *x != gap).map(move |x| (x, y))
+            })
+            .collect();
+        let maze = build_maze(&walls, width, height);
+        let mut seen = vec![vec![usize::MAX; width]; height];
+        let steps = find_shortest_path(&maze, &mut seen).unwrap();
+        assert_eq!(steps, (221 * 299) + (220 * 2));
+        assert!(steps > u16::MAX as usize);
+    }
 }
@@ -2,6 +2,8 @@
 
 use std::fs;
 
+use crate::util::bigint::Count;
+
 #[derive(Debug)]
 struct PrefixTrieNode {
     c: u8,
@@ -65,29 +67,43 @@ fn parse_input(filename: &str) -> (PrefixTrieNode, String) {
     (prefix_trie, designs)
 }
 
-fn ways_design_is_producible(prefix_trie: &PrefixTrieNode, design: &[u8]) -> usize {
-    let mut visited = vec![0usize; design.len() + 1];
-    visited[0] = 1;
+fn ways_design_is_producible<C: Count>(prefix_trie: &PrefixTrieNode, design: &[u8]) -> C {
+    let mut visited = vec![C::default(); design.len() + 1];
+    visited[0] = C::one();
     for i in 0..visited.len() {
-        if visited[i] == 0 {
+        if visited[i].is_zero() {
             continue;
         }
         for len in prefix_trie.matching_substr_lengths(&design[i..]) {
-            visited[i + len] += visited[i];
+            let ways = visited[i].clone();
+            visited[i + len] += ways;
+        }
+    }
+    visited.pop().unwrap()
+}
+
+fn design_is_producible(prefix_trie: &PrefixTrieNode, design: &[u8]) -> bool {
+    let mut reachable = vec![false; design.len() + 1];
+    reachable[0] = true;
+    for i in 0..design.len() {
+        if reachable[i] {
+            for len in prefix_trie.matching_substr_lengths(&design[i..]) {
+                reachable[i + len] = true;
+            }
         }
     }
-    visited[design.len()]
+    reachable[design.len()]
 }
 
 fn count_producible_designs(filename: &str) -> usize {
     let (prefix_trie, design_str) = parse_input(filename);
     design_str
         .split_whitespace()
-        .filter(|design| ways_design_is_producible(&prefix_trie, design.as_bytes()) > 0)
+        .filter(|design| design_is_producible(&prefix_trie, design.as_bytes()))
         .count()
 }
 
-fn sum_ways_designs_are_producible(filename: &str) -> usize {
+fn sum_ways_designs_are_producible<C: Count>(filename: &str) -> C {
     let (prefix_trie, design_str) = parse_input(filename);
     design_str
         .split_whitespace()
@@ -97,7 +113,12 @@ fn sum_ways_designs_are_producible(filename: &str) -> usize {
 
 #[cfg(test)]
 mod tests {
-    use super::{count_producible_designs, sum_ways_designs_are_producible};
+    use crate::util::bigint::BigUint;
+
+    use super::{
+        count_producible_designs, sum_ways_designs_are_producible, ways_design_is_producible,
+        PrefixTrieNode,
+    };
 
     #[test]
     fn part1_example() {
@@ -113,13 +134,26 @@ mod tests {
 
     #[test]
     fn part2_example() {
-        let result = sum_ways_designs_are_producible("example.txt");
+        let result = sum_ways_designs_are_producible::<usize>("example.txt");
         assert_eq!(result, 16);
     }
 
     #[test]
     fn part2() {
-        let result = sum_ways_designs_are_producible("input.txt");
+        let result = sum_ways_designs_are_producible::<usize>("input.txt");
         assert_eq!(result, 622121814629343);
     }
+
+    #[test]
+    fn long_design_exact() {
+        let mut prefix_trie = PrefixTrieNode::new(0);
+        prefix_trie.insert(b"r");
+        prefix_trie.insert(b"rr");
+        // the 301st Fibonacci number, far beyond u128
+        let result: BigUint = ways_design_is_producible(&prefix_trie, &[b'r'; 300]);
+        assert_eq!(
+            result.to_string(),
+            "359579325206583560961765665172189099052367214309267232255589801"
+        );
+    }
 }
@@ -1,4 +1,5 @@
 use std::{
+    fmt,
     fs::File,
     io::{BufRead, BufReader},
 };
@@ -17,52 +18,98 @@ fn parse_input(filename: &str) -> Vec<Vec<i32>> {
         .collect()
 }
 
-fn is_valid_window(window: &[i32], ascending: bool) -> bool {
-    assert_eq!(window.len(), 3);
-    let direction = (ascending && window[0] < window[1] && window[1] < window[2])
-        || (!ascending && window[0] > window[1] && window[1] > window[2]);
-    let magnitude = (1..=3).contains(&window[0].abs_diff(window[1]))
-        && (1..=3).contains(&window[1].abs_diff(window[2]));
-    direction && magnitude
+/// Why a report is unsafe, as the first bad step between `at` and `at + 1`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StepViolation {
+    DirectionChange { at: usize },
+    ZeroStep { at: usize },
+    Jump { at: usize, size: u32 },
+}
+impl fmt::Display for StepViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DirectionChange { at } => {
+                write!(f, "levels {at} to {} change direction", at + 1)
+            }
+            Self::ZeroStep { at } => write!(f, "levels {at} and {} are equal", at + 1),
+            Self::Jump { at, size } => {
+                write!(f, "levels {at} to {} jump by {size}", at + 1)
+            }
+        }
+    }
 }
 
-fn is_report_safe_when_removing_i(report: &[i32], mut dampened: bool, to_remove: usize) -> bool {
-    assert!(report.len() > 2);
-    assert!(to_remove < 3);
-    let mut window: Vec<i32> = report.iter().take(3).copied().collect();
-    let mut ascending = window[0] < window[1];
-    if !is_valid_window(&window, ascending) {
-        if dampened {
-            dampened = false;
-            window.remove(to_remove);
-            ascending = window[0] < window[1];
+fn first_violation(report: &[i32]) -> Option<StepViolation> {
+    let ascending = report.len() > 1 && report[0] < report[1];
+    report.windows(2).enumerate().find_map(|(at, w)| {
+        let size = w[0].abs_diff(w[1]);
+        if size == 0 {
+            Some(StepViolation::ZeroStep { at })
+        } else if (w[0] < w[1]) != ascending {
+            Some(StepViolation::DirectionChange { at })
+        } else if size > 3 {
+            Some(StepViolation::Jump { at, size })
         } else {
-            return false;
+            None
         }
-    } else {
-        window.remove(0);
+    })
+}
+
+// The fewest levels to remove so the rest step 1 to 3 in the one direction,
+// if that's at most `max_removals`. A kept level can only follow one of the
+// `max_removals + 1` levels before it, so this is O(n * k).
+fn fewest_removals(report: &[i32], ascending: bool, max_removals: usize) -> Option<Vec<usize>> {
+    let n = report.len();
+    if n == 0 {
+        return Some(Vec::new());
     }
-    for level in report.iter().skip(3) {
-        window.push(*level);
-        if !is_valid_window(&window, ascending) {
-            if dampened {
-                dampened = false;
-                window.remove(to_remove);
-            } else {
-                return false;
+    let step_ok = |a: i32, b: i32| (1..=3).contains(&if ascending { b - a } else { a - b });
+    // removals[i] counts levels dropped before i when i is kept, prev[i] is the kept level before it
+    let mut removals = vec![usize::MAX; n];
+    let mut prev = vec![None; n];
+    for i in 0..n {
+        for j in i.saturating_sub(max_removals + 1)..i {
+            if removals[j] == usize::MAX || !step_ok(report[j], report[i]) {
+                continue;
             }
-        } else {
-            window.remove(0);
+            let cost = removals[j] + (i - j - 1);
+            if cost <= max_removals && cost < removals[i] {
+                removals[i] = cost;
+                prev[i] = Some(j);
+            }
+        }
+        if i <= max_removals && i < removals[i] {
+            removals[i] = i;
+            prev[i] = None;
         }
     }
-    (1..=3).contains(&window[0].abs_diff(window[1]))
+    let last = ((n - 1).saturating_sub(max_removals)..n)
+        .filter(|i| removals[*i] != usize::MAX && removals[*i] + (n - 1 - i) <= max_removals)
+        .min_by_key(|i| removals[*i] + (n - 1 - i))?;
+    let mut kept = vec![false; n];
+    let mut cur = Some(last);
+    while let Some(i) = cur {
+        kept[i] = true;
+        cur = prev[i];
+    }
+    Some((0..n).filter(|i| !kept[*i]).collect())
+}
+
+/// The indices to remove so the report is safe, removing at most `max_removals`,
+/// or the first thing wrong with the report as it stands.
+fn check_report(report: &[i32], max_removals: usize) -> Result<Vec<usize>, StepViolation> {
+    let ascending = fewest_removals(report, true, max_removals);
+    let descending = fewest_removals(report, false, max_removals);
+    match (ascending, descending) {
+        (Some(a), Some(d)) => Ok(if d.len() < a.len() { d } else { a }),
+        (Some(removed), None) | (None, Some(removed)) => Ok(removed),
+        (None, None) => Err(first_violation(report).unwrap()),
+    }
 }
 
 #[allow(dead_code)]
 fn is_report_safe(report: &[i32], dampened: bool) -> bool {
-    is_report_safe_when_removing_i(report, dampened, 0)
-        || is_report_safe_when_removing_i(report, dampened, 1)
-        || is_report_safe_when_removing_i(report, dampened, 2)
+    check_report(report, dampened as usize).is_ok()
 }
 
 #[allow(dead_code)]
@@ -76,7 +123,7 @@ fn count_safe_reports(filename: &str, dampened: bool) -> usize {
 
 #[cfg(test)]
 mod tests {
-    use super::{count_safe_reports, is_report_safe, parse_input};
+    use super::{check_report, count_safe_reports, is_report_safe, parse_input, StepViolation};
 
     #[test]
     fn part1_example() {
@@ -136,4 +183,30 @@ mod tests {
         let result = count_safe_reports("more_ex.txt", true);
         assert_eq!(result, 12);
     }
+
+    #[test]
+    fn removes_up_to_k() {
+        let report = [1, 2, 9, 3, 9, 4, 5];
+        assert_eq!(
+            check_report(&report, 1),
+            Err(StepViolation::Jump { at: 1, size: 7 })
+        );
+        assert_eq!(check_report(&report, 2), Ok(vec![2, 4]));
+        assert_eq!(check_report(&[9, 7, 8, 6, 5], 1), Ok(vec![2]));
+        assert_eq!(check_report(&[1, 3, 5], 0), Ok(vec![]));
+        assert_eq!(check_report(&[4, 4], 1), Ok(vec![1]));
+        assert_eq!(check_report(&[1, 2], 5), Ok(vec![]));
+    }
+
+    #[test]
+    fn explains_violations() {
+        let report = [69, 67, 66, 67, 70, 71, 74, 75];
+        let violation = check_report(&report, 1).unwrap_err();
+        assert_eq!(violation, StepViolation::DirectionChange { at: 2 });
+        assert_eq!(violation.to_string(), "levels 2 to 3 change direction");
+        assert_eq!(
+            check_report(&[1, 1, 1, 1], 1),
+            Err(StepViolation::ZeroStep { at: 0 })
+        );
+    }
 }
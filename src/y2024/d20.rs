@@ -3,6 +3,11 @@ use std::fs;
 
 use itertools::Itertools;
 
+use crate::util::{
+    parallel::Parallel,
+    validate::{validate, Check, Violation},
+};
+
 struct Maze<T: Copy> {
     maze: Vec<T>,
     width: usize,
@@ -20,21 +25,92 @@ impl<T: Copy> Maze<T> {
     }
 }
 
-fn load_maze(filename: &str) -> Maze<u8> {
+impl Maze<u8> {
+    fn open_neighbours(&self, x: i64, y: i64) -> usize {
+        [(0, -1), (1, 0), (0, 1), (-1, 0)]
+            .iter()
+            .filter(|(dx, dy)| self.get(x + dx, y + dy) != b'#')
+            .count()
+    }
+
+    fn cells(&self) -> impl Iterator<Item = (i64, i64)> + '_ {
+        (0..self.height as i64).flat_map(move |y| (0..self.width as i64).map(move |x| (x, y)))
+    }
+}
+
+// run on the text first, so the grid checks can index freely
+const TEXT_CHECKS: &[Check<str>] = &[Check {
+    assumption: "every row has the same width",
+    test: |contents| {
+        let width = contents.lines().next().map_or(0, str::len);
+        match contents.lines().position(|line| line.len() != width) {
+            Some(y) => Err(format!("row {y} isn't {width} wide like row 0")),
+            None => Ok(()),
+        }
+    },
+}];
+
+const MAZE_CHECKS: &[Check<Maze<u8>>] = &[
+    // the walk and the cheat scan only bounds check against the walls
+    Check {
+        assumption: "maze is surrounded by walls",
+        test: |maze| {
+            let (w, h) = (maze.width as i64, maze.height as i64);
+            match maze.cells().find(|(x, y)| {
+                (*x == 0 || *y == 0 || *x == w - 1 || *y == h - 1) && maze.get(*x, *y) != b'#'
+            }) {
+                Some((x, y)) => Err(format!("({x}, {y}) on the edge is open")),
+                None => Ok(()),
+            }
+        },
+    },
+    Check {
+        assumption: "maze has one start and one end",
+        test: |maze| {
+            let count = |c| maze.maze.iter().filter(|b| **b == c).count();
+            match (count(b'S'), count(b'E')) {
+                (1, 1) => Ok(()),
+                (s, e) => Err(format!("{s} starts and {e} ends")),
+            }
+        },
+    },
+    // walk_maze takes the first unvisited opening, so a branch would lose the real path
+    Check {
+        assumption: "track is a single corridor",
+        test: |maze| {
+            for (x, y) in maze.cells() {
+                let allowed = match maze.get(x, y) {
+                    b'#' => continue,
+                    b'S' | b'E' => 1,
+                    _ => 2,
+                };
+                let open = maze.open_neighbours(x, y);
+                if open > allowed {
+                    return Err(format!("({x}, {y}) has {open} open neighbours"));
+                }
+            }
+            Ok(())
+        },
+    },
+];
+
+fn load_maze(filename: &str) -> Result<Maze<u8>, Violation> {
     let contents = fs::read_to_string("input/2024/20/".to_owned() + filename).unwrap();
-    let mut width = 0;
+    validate(&contents[..], TEXT_CHECKS)?;
+    let width = contents.lines().next().map_or(0, str::len);
     let mut height = 0;
     let mut maze = Vec::new();
     for line in contents.lines() {
-        width = line.len();
         height += 1;
         maze.extend(line.bytes());
     }
-    Maze {
+    let maze = Maze {
         maze,
         width,
         height,
-    }
+    };
+    validate(&maze, MAZE_CHECKS)?;
+    Ok(maze)
 }
 
 fn walk_maze(maze: &Maze<u8>, seen: &mut Maze<usize>) -> Vec<(i64, i64)> {
@@ -63,8 +139,12 @@ fn walk_maze(maze: &Maze<u8>, seen: &mut Maze<usize>) -> Vec<(i64, i64)> {
     walked
 }
 
-fn shortcuts_that_save_over_x(filename: &str, cheat_len: i64, min_saved: usize) -> usize {
-    let maze = load_maze(filename);
+fn shortcuts_that_save_over_x(
+    filename: &str,
+    cheat_len: i64,
+    min_saved: usize,
+) -> Result<usize, Violation> {
+    let maze = load_maze(filename)?;
     let mut seen = Maze {
         maze: vec![usize::MAX; maze.width * maze.height],
         width: maze.width,
@@ -75,12 +155,13 @@ fn shortcuts_that_save_over_x(filename: &str, cheat_len: i64, min_saved: usize)
     let walked = walk_maze(&maze, &mut seen);
 
     // now do shortcuts
-    let mut qualifying_count = 0;
-    for (x, y) in walked {
-        qualifying_count += process_pos(&maze, &seen, cheat_len, x, y, min_saved);
-    }
-
-    qualifying_count
+    Ok(Parallel::new()
+        .map_reduce(
+            &walked,
+            |(x, y)| process_pos(&maze, &seen, cheat_len, *x, *y, min_saved),
+            |a, b| a + b,
+        )
+        .unwrap_or(0))
 }
 
 struct CheatState {
@@ -155,35 +236,57 @@ fn process_pos(
 
 #[cfg(test)]
 mod tests {
-    use super::shortcuts_that_save_over_x;
+    use crate::util::validate::validate;
+
+    use super::{shortcuts_that_save_over_x, Maze, MAZE_CHECKS, TEXT_CHECKS};
 
     #[test]
     fn part1_example() {
-        let result = shortcuts_that_save_over_x("example.txt", 2, 20);
+        let result = shortcuts_that_save_over_x("example.txt", 2, 20).unwrap();
         assert_eq!(result, 5);
     }
 
     #[test]
     fn part1() {
-        let result = shortcuts_that_save_over_x("input.txt", 2, 100);
+        let result = shortcuts_that_save_over_x("input.txt", 2, 100).unwrap();
         assert_eq!(result, 1307);
     }
 
     #[test]
     fn part2_example_70() {
-        let result = shortcuts_that_save_over_x("example.txt", 20, 70);
+        let result = shortcuts_that_save_over_x("example.txt", 20, 70).unwrap();
         assert_eq!(result, 41);
     }
 
     #[test]
     fn part2_example_76() {
-        let result = shortcuts_that_save_over_x("example.txt", 20, 76);
+        let result = shortcuts_that_save_over_x("example.txt", 20, 76).unwrap();
         assert_eq!(result, 3);
     }
 
     #[test]
     fn part2() {
-        let result = shortcuts_that_save_over_x("input.txt", 20, 100);
+        let result = shortcuts_that_save_over_x("input.txt", 20, 100).unwrap();
         assert_eq!(result, 986545);
     }
+
+    #[test]
+    fn rejects_second_route() {
+        let rows = ["#####", "#S..#", "#.#.#", "#..E#", "#####"];
+        let maze = Maze {
+            maze: rows.iter().flat_map(|r| r.bytes()).collect(),
+            width: 5,
+            height: 5,
+        };
+        let violation = validate(&maze, MAZE_CHECKS).unwrap_err();
+        assert_eq!(violation.assumption, "track is a single corridor");
+        assert_eq!(violation.detail, "(1, 1) has 2 open neighbours");
+    }
+
+    #[test]
+    fn rejects_ragged_rows() {
+        let violation = validate("####\n#SE\n####", TEXT_CHECKS).unwrap_err();
+        assert_eq!(violation.assumption, "every row has the same width");
+        assert_eq!(violation.detail, "row 1 isn't 4 wide like row 0");
+    }
 }
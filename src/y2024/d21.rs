@@ -4,6 +4,8 @@ use std::{collections::HashMap, fs};
 
 use itertools::Itertools;
 
+use crate::util::memo::Memo;
+
 fn load_input(filename: &str) -> Vec<String> {
     let contents = fs::read_to_string("input/2024/21/".to_owned() + filename).unwrap();
     contents.lines().map(|x| x.to_string()).collect()
@@ -167,7 +169,7 @@ fn build_keypad_paths() -> HashMap<(char, char), Vec<String>> {
 }
 
 fn generate(
-    memo: &mut HashMap<(char, char, usize), usize>,
+    memo: &mut Memo<(char, char, usize), usize>,
     path: &str,
     paths: &HashMap<(char, char), Vec<String>>,
     level: usize,
@@ -178,23 +180,15 @@ fn generate(
     let mut current = 'A';
     let mut generated = 0;
     for c in path.chars() {
-        let shortest = match memo.get(&(current, c, level)) {
-            Some(s) => *s,
-            None => {
-                let paths0 = paths.get(&(current, c)).unwrap();
-                let shortest = paths0
-                    .iter()
-                    .map(|p| generate(memo, p, paths, level - 1))
-                    .min()
-                    .unwrap();
-                if level <= 20 {
-                    memo.insert((current, c, level), shortest);
-                }
-                //println!("{level}: {}, {}", memo.len(), shortest.len());
-                shortest
-            }
-        };
-        generated += &shortest;
+        generated += memo.get_or_compute((current, c, level), |memo| {
+            paths
+                .get(&(current, c))
+                .unwrap()
+                .iter()
+                .map(|p| generate(memo, p, paths, level - 1))
+                .min()
+                .unwrap()
+        });
         current = c;
     }
     generated
@@ -203,7 +197,7 @@ fn generate(
 fn shortest_generated_sequence_len(
     input: &str,
     levels: usize,
-    memo: &mut HashMap<(char, char, usize), usize>,
+    memo: &mut Memo<(char, char, usize), usize>,
 ) -> usize {
     let numpad_paths = build_numpad_paths();
     let keypad_paths = build_keypad_paths();
@@ -300,7 +294,7 @@ fn num_part(s: &str) -> usize {
 
 pub fn situation(filename: &str, levels: usize) -> usize {
     let inputs = load_input(filename);
-    let mut memo = HashMap::new();
+    let mut memo = Memo::new();
     inputs
         .iter()
         .map(|input| shortest_generated_sequence_len(input, levels, &mut memo) * num_part(input))
@@ -327,11 +321,11 @@ impl Key {
 #[cfg(test)]
 mod tests {
     use super::{load_input, num_part, shortest_generated_sequence_len, situation};
-    use std::collections::HashMap;
+    use crate::util::memo::Memo;
 
     #[test]
     fn first_example() {
-        let mut memo = HashMap::new();
+        let mut memo = Memo::new();
         let result = shortest_generated_sequence_len("029A", 2, &mut memo);
         assert_eq!(
             result,
@@ -341,7 +335,7 @@ mod tests {
 
     #[test]
     fn last_example() {
-        let mut memo = HashMap::new();
+        let mut memo = Memo::new();
         let result = shortest_generated_sequence_len("379A", 2, &mut memo);
         assert_eq!(
             result,
@@ -359,7 +353,7 @@ mod tests {
     #[test]
     fn seq_lens() {
         let inputs = load_input("example.txt");
-        let mut memo = HashMap::new();
+        let mut memo = Memo::new();
         let result: Vec<_> = inputs
             .iter()
             .map(|s| shortest_generated_sequence_len(s, 2, &mut memo))
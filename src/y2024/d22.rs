@@ -2,6 +2,8 @@
 
 use std::{collections::HashMap, fs};
 
+use crate::util::parallel::Parallel;
+
 fn get_inputs(filename: &str) -> Vec<i64> {
     let contents = fs::read_to_string("input/2024/22/".to_owned() + filename).unwrap();
     contents
@@ -72,9 +74,13 @@ impl Iterator for PriceChangeSeqs {
 
 fn sum_secret_numbers_after_steps(filename: &str, steps: usize) -> i64 {
     let nums = get_inputs(filename);
-    nums.into_iter()
-        .map(|n| SecretNumberSeq::new(n).nth(steps).unwrap())
-        .sum()
+    Parallel::new()
+        .map_reduce(
+            &nums,
+            |n| SecretNumberSeq::new(*n).nth(steps).unwrap(),
+            |a, b| a + b,
+        )
+        .unwrap_or(0)
 }
 
 #[derive(Hash, Eq, PartialEq, Clone, Debug)]
@@ -99,20 +105,24 @@ impl Seq4 {
 
 pub fn sell_for_bananas_better(filename: &str) -> i64 {
     let nums = get_inputs(filename);
-    let mut seen: HashMap<Seq4, (usize, i64)> = HashMap::new();
-    for (i, num) in nums.iter().enumerate() {
+    // each buyer sells at the first time a sequence shows up
+    let first_prices = |num: &i64| {
+        let mut prices = HashMap::new();
         for (seq, price) in SecretNumberSeq::new(*num).sequences().take(1995) {
-            seen.entry(seq)
-                .and_modify(|data| {
-                    if data.0 < i {
-                        data.0 = i;
-                        data.1 += price;
-                    }
-                })
-                .or_insert((i, price));
+            prices.entry(seq).or_insert(price);
         }
-    }
-    seen.values().map(|(_, price)| *price).max().unwrap()
+        prices
+    };
+    let totals = Parallel::new().map_reduce(&nums, first_prices, |mut a, mut b| {
+        if a.len() < b.len() {
+            std::mem::swap(&mut a, &mut b);
+        }
+        for (seq, price) in b {
+            *a.entry(seq).or_insert(0) += price;
+        }
+        a
+    });
+    totals.unwrap().into_values().max().unwrap()
 }
 
 #[cfg(test)]
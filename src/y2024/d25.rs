@@ -56,6 +56,7 @@ impl KeyTrie {
 
 use std::{collections::HashSet, fs};
 
+#[allow(clippy::needless_range_loop)]
 fn parse_lock(s: &str) -> u16 {
     let mut num = 0;
     let lines = s.split_whitespace();
@@ -71,6 +72,7 @@ fn parse_lock(s: &str) -> u16 {
     num
 }
 
+#[allow(clippy::needless_range_loop)]
 fn parse_key(s: &str) -> u16 {
     let mut num = 0;
     let lines = s.split_whitespace();
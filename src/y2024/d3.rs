@@ -1,8 +1,15 @@
-use std::{fs::File, io::Read};
+#![allow(dead_code)]
 
-use regex::Regex;
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, Read},
+};
+
+// Limits on how much of a call is looked at. They keep the streaming
+// interpreter's carry-over between chunks bounded.
+const MAX_NAME: usize = 32;
+const MAX_ARGS: usize = 8;
 
-#[allow(dead_code)]
 fn get_input(filename: &str) -> String {
     let mut file = File::open("input/2024/3/".to_owned() + filename).unwrap();
     let mut contents = String::new();
@@ -10,65 +17,301 @@ fn get_input(filename: &str) -> String {
     contents
 }
 
-#[allow(dead_code)]
-fn add_mutliply_instructions(filename: &str) -> u32 {
-    let input = get_input(filename);
-    let re = Regex::new(r"mul\((\d{1,3}),(\d{1,3})\)").unwrap();
-    re.captures_iter(&input)
-        .map(|c| {
-            let captures: (&str, [&str; 2]) = c.extract();
-            let a = captures.1[0].parse::<u32>().unwrap();
-            let b = captures.1[1].parse::<u32>().unwrap();
-            a * b
-        })
-        .sum()
+/// A well formed call such as `mul(2,4)` or `what()`, found at byte `offset`.
+/// `name` is the run of name characters before the bracket (at most the last
+/// `MAX_NAME` of them), so it may carry junk in front of the instruction, as
+/// in `xmul(2,4)`. `text` is the call as written, starting with `name`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Token<'a> {
+    offset: usize,
+    name: &'a str,
+    args: Vec<u32>,
+    text: &'a str,
+}
+
+fn is_name_byte(b: u8) -> bool {
+    b.is_ascii_lowercase() || b == b'\'' || b == b'_'
+}
+
+/// Finds call tokens in one pass, skipping everything in between.
+struct Lexer<'a> {
+    input: &'a [u8],
+    pos: usize,
+    // offset of input[0] in the whole memory dump
+    base: usize,
+    // whether more input may follow
+    partial: bool,
+}
+impl<'a> Lexer<'a> {
+    fn new(input: &'a str) -> Self {
+        Lexer {
+            input: input.as_bytes(),
+            pos: 0,
+            base: 0,
+            partial: false,
+        }
+    }
+
+    /// Lexes one chunk of a longer dump. Stops at a call that runs off the end,
+    /// leaving `resume_at` pointing at it so it can be retried with more input.
+    fn partial(input: &'a [u8], base: usize) -> Self {
+        Lexer {
+            input,
+            pos: 0,
+            base,
+            partial: true,
+        }
+    }
+
+    fn resume_at(&self) -> usize {
+        self.pos
+    }
+
+    /// No more input is coming, so a call running off the end is just corrupt.
+    fn with_end(self) -> Self {
+        Lexer {
+            partial: false,
+            ..self
+        }
+    }
+
+    // the arguments and the end of the call, or where it stopped making sense
+    fn call_after(&self, open: usize) -> Result<(Vec<u32>, usize), usize> {
+        let input = self.input;
+        if input.get(open) != Some(&b'(') {
+            return Err(open);
+        }
+        let mut args = Vec::new();
+        let mut i = open + 1;
+        if input.get(i) == Some(&b')') {
+            return Ok((args, i + 1));
+        }
+        loop {
+            let digits = input[i..]
+                .iter()
+                .take(4)
+                .take_while(|b| b.is_ascii_digit())
+                .count();
+            if !(1..=3).contains(&digits) {
+                return Err(i + digits);
+            }
+            let arg = std::str::from_utf8(&input[i..i + digits]).unwrap();
+            args.push(arg.parse().unwrap());
+            i += digits;
+            match input.get(i) {
+                Some(b',') if args.len() < MAX_ARGS => i += 1,
+                Some(b')') => return Ok((args, i + 1)),
+                _ => return Err(i),
+            }
+        }
+    }
+}
+impl<'a> Iterator for Lexer<'a> {
+    type Item = Token<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.pos < self.input.len() {
+            let start = self.pos;
+            if !is_name_byte(self.input[start]) {
+                self.pos += 1;
+                continue;
+            }
+            let name_end = start
+                + self.input[start..]
+                    .iter()
+                    .take_while(|b| is_name_byte(**b))
+                    .count();
+            let start = start.max(name_end.saturating_sub(MAX_NAME));
+            let text = |end| std::str::from_utf8(&self.input[start..end]).unwrap();
+            match self.call_after(name_end) {
+                Ok((args, end)) => {
+                    self.pos = end;
+                    return Some(Token {
+                        offset: self.base + start,
+                        name: text(name_end),
+                        args,
+                        text: text(end),
+                    });
+                }
+                Err(stop) if stop == self.input.len() && self.partial => {
+                    self.pos = start;
+                    return None;
+                }
+                // nothing consumed so far can start a token, so pick up where it failed
+                Err(stop) => self.pos = stop,
+            }
+        }
+        None
+    }
+}
+
+#[derive(Debug)]
+struct Machine {
+    enabled: bool,
+    total: u32,
+}
+
+/// Something the interpreter can run. Adding one is a matter of listing it
+/// alongside the others.
+struct Instruction {
+    name: &'static str,
+    arity: usize,
+    /// Whether `don't()` stops this from running.
+    conditional: bool,
+    run: fn(&mut Machine, &[u32]),
+}
+
+const MUL: Instruction = Instruction {
+    name: "mul",
+    arity: 2,
+    conditional: true,
+    run: |m, args| m.total += args[0] * args[1],
+};
+const DO: Instruction = Instruction {
+    name: "do",
+    arity: 0,
+    conditional: false,
+    run: |m, _| m.enabled = true,
+};
+const DONT: Instruction = Instruction {
+    name: "don't",
+    arity: 0,
+    conditional: false,
+    run: |m, _| m.enabled = false,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Outcome {
+    Executed,
+    Disabled,
+    Ignored,
+}
+
+/// What happened to a call, with its position and source text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Event {
+    offset: usize,
+    text: String,
+    outcome: Outcome,
 }
 
 #[derive(Debug)]
-enum Instruction {
-    Do,
-    Dont,
-    Mul(u32, u32),
-}
-
-fn get_instructions(input: &str) -> Vec<Instruction> {
-    let all_re = Regex::new(r"mul\(\d{1,3},\d{1,3}\)|don't\(\)|do\(\)").unwrap();
-    let multiply_re = Regex::new(r"mul\((\d{1,3}),(\d{1,3})\)").unwrap();
-    all_re
-        .find_iter(input)
-        .map(|m| match m.as_str() {
-            "don't()" => Instruction::Dont,
-            "do()" => Instruction::Do,
-            mul => {
-                let captures: [&str; 2] = multiply_re.captures(mul).unwrap().extract().1;
-                let a = captures[0].parse::<u32>().unwrap();
-                let b = captures[1].parse::<u32>().unwrap();
-                Instruction::Mul(a, b)
+struct Report {
+    total: u32,
+    events: Vec<Event>,
+}
+
+struct Interpreter<'i> {
+    machine: Machine,
+    instructions: &'i [Instruction],
+}
+impl<'i> Interpreter<'i> {
+    fn new(instructions: &'i [Instruction]) -> Self {
+        Interpreter {
+            machine: Machine {
+                enabled: true,
+                total: 0,
+            },
+            instructions,
+        }
+    }
+
+    fn step(&mut self, token: &Token) -> Event {
+        // the longest instruction name the call ends with, so `xmul` runs `mul`
+        let found = self
+            .instructions
+            .iter()
+            .filter(|ins| token.name.ends_with(ins.name) && ins.arity == token.args.len())
+            .max_by_key(|ins| ins.name.len());
+        let Some(ins) = found else {
+            return Event {
+                offset: token.offset,
+                text: token.text.to_string(),
+                outcome: Outcome::Ignored,
+            };
+        };
+        let junk = token.name.len() - ins.name.len();
+        let outcome = if ins.conditional && !self.machine.enabled {
+            Outcome::Disabled
+        } else {
+            (ins.run)(&mut self.machine, &token.args);
+            Outcome::Executed
+        };
+        Event {
+            offset: token.offset + junk,
+            text: token.text[junk..].to_string(),
+            outcome,
+        }
+    }
+}
+
+fn interpret(input: &str, instructions: &[Instruction]) -> Report {
+    let mut interpreter = Interpreter::new(instructions);
+    let events = Lexer::new(input)
+        .map(|token| interpreter.step(&token))
+        .collect();
+    Report {
+        total: interpreter.machine.total,
+        events,
+    }
+}
+
+/// Same as `interpret`, but reads the dump a buffer at a time, so memory use
+/// doesn't grow with its size. Events are passed to `on_event` rather than kept.
+fn interpret_stream(
+    mut reader: impl BufRead,
+    instructions: &[Instruction],
+    mut on_event: impl FnMut(Event),
+) -> io::Result<u32> {
+    let mut interpreter = Interpreter::new(instructions);
+    // whatever is left of the previous chunk, then the new one
+    let mut pending = Vec::new();
+    let mut base = 0;
+    loop {
+        let chunk = reader.fill_buf()?;
+        let read = chunk.len();
+        pending.extend_from_slice(chunk);
+        reader.consume(read);
+        if read == 0 {
+            for token in Lexer::partial(&pending, base).with_end() {
+                on_event(interpreter.step(&token));
             }
-        })
-        .collect()
+            return Ok(interpreter.machine.total);
+        }
+        let mut lexer = Lexer::partial(&pending, base);
+        for token in lexer.by_ref() {
+            on_event(interpreter.step(&token));
+        }
+        let done = lexer.resume_at();
+        pending.drain(..done);
+        base += done;
+    }
+}
+
+fn add_mutliply_instructions(filename: &str) -> u32 {
+    let input = get_input(filename);
+    interpret(&input, &[MUL]).total
 }
 
-#[allow(dead_code)]
 fn sum_multiplies_with_instructions(filename: &str) -> u32 {
     let input = get_input(filename);
-    let instructions = get_instructions(&input);
-    let mut do_state = true;
-    let mut multiplies = Vec::new();
-    for ins in instructions {
-        match (ins, do_state) {
-            (Instruction::Do, _) => do_state = true,
-            (Instruction::Dont, _) => do_state = false,
-            (Instruction::Mul(a, b), true) => multiplies.push(a * b),
-            (Instruction::Mul(_, _), false) => (),
-        }
-    }
-    multiplies.iter().sum()
+    interpret(&input, &[MUL, DO, DONT]).total
+}
+
+fn sum_multiplies_streaming(filename: &str, buffer_size: usize) -> io::Result<u32> {
+    let file = File::open("input/2024/3/".to_owned() + filename)?;
+    let reader = BufReader::with_capacity(buffer_size, file);
+    interpret_stream(reader, &[MUL, DO, DONT], |_| ())
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{add_mutliply_instructions, sum_multiplies_with_instructions};
+    use std::io::{BufRead, BufReader, Read};
+
+    use super::{
+        add_mutliply_instructions, interpret, interpret_stream, sum_multiplies_streaming,
+        sum_multiplies_with_instructions, Event, Instruction, Lexer, Outcome, DO, DONT, MUL,
+    };
 
     #[test]
     fn part1_example() {
@@ -93,4 +336,84 @@ mod tests {
         let result = sum_multiplies_with_instructions("input.txt");
         assert_eq!(result, 98729041);
     }
+
+    #[test]
+    fn lexer_offsets() {
+        let tokens: Vec<_> = Lexer::new("%mul(1,2mul(3,4)!x(1234)?don't()")
+            .map(|t| (t.offset, t.name, t.args))
+            .collect();
+        assert_eq!(tokens, [(8, "mul", vec![3, 4]), (25, "don't", vec![])]);
+    }
+
+    #[test]
+    fn report_every_call() {
+        let input = "xmul(2,4)&what()don't()_mul(5,5)+do()mul(8,5)";
+        let report = interpret(input, &[MUL, DO, DONT]);
+        let event = |offset, text: &str, outcome| Event {
+            offset,
+            text: text.to_string(),
+            outcome,
+        };
+        assert_eq!(
+            report.events,
+            [
+                event(1, "mul(2,4)", Outcome::Executed),
+                event(10, "what()", Outcome::Ignored),
+                event(16, "don't()", Outcome::Executed),
+                event(24, "mul(5,5)", Outcome::Disabled),
+                event(33, "do()", Outcome::Executed),
+                event(37, "mul(8,5)", Outcome::Executed),
+            ]
+        );
+        assert_eq!(report.total, 48);
+    }
+
+    #[test]
+    fn custom_instruction() {
+        const ADD: Instruction = Instruction {
+            name: "add",
+            arity: 3,
+            conditional: true,
+            run: |m, args| m.total += args.iter().sum::<u32>(),
+        };
+        let report = interpret("add(1,2,3)mul(2,2)add(1)", &[MUL, ADD]);
+        assert_eq!(report.total, 10);
+        assert_eq!(report.events[2].outcome, Outcome::Ignored);
+    }
+
+    #[test]
+    fn part2_streaming() {
+        let result = sum_multiplies_streaming("input.txt", 7).unwrap();
+        assert_eq!(result, 98729041);
+    }
+
+    fn stream_events(reader: impl BufRead) -> (u32, Vec<Event>) {
+        let mut events = Vec::new();
+        let total = interpret_stream(reader, &[MUL, DO, DONT], |e| events.push(e)).unwrap();
+        (total, events)
+    }
+
+    #[test]
+    fn tokens_split_across_chunks() {
+        let (total, events) =
+            stream_events(b"xmu".chain(&b"l(12,3)don"[..]).chain(&b"'t()mul(2,2)"[..]));
+        assert_eq!(total, 36);
+        let texts: Vec<_> = events.iter().map(|e| (e.offset, e.text.as_str())).collect();
+        assert_eq!(texts, [(1, "mul(12,3)"), (10, "don't()"), (17, "mul(2,2)")]);
+        assert_eq!(events[2].outcome, Outcome::Disabled);
+    }
+
+    #[test]
+    fn streaming_matches_in_memory() {
+        let mut input =
+            "xmul(2,4)&what()don't()_mul(5,5)+do()mul(8,5)mul(1,2,3,4,5,6,7,8,9)".repeat(3);
+        input.push_str(&"a".repeat(100));
+        input.push_str("mul(3,3)mul(4");
+        let expected = interpret(&input, &[MUL, DO, DONT]);
+        for size in 1..=20 {
+            let (total, events) = stream_events(BufReader::with_capacity(size, input.as_bytes()));
+            assert_eq!(total, expected.total, "buffer size {size}");
+            assert_eq!(events, expected.events, "buffer size {size}");
+        }
+    }
 }
@@ -12,103 +12,149 @@ fn load_input(filename: &str) -> Vec<Vec<u8>> {
         .collect()
 }
 
-#[allow(dead_code)]
-fn count_xmas_occurrences(filename: &str) -> u32 {
-    let lines = load_input(filename);
-    let mut count = 0;
-    for i in 0..lines.len() {
-        for j in 0..lines[i].len() {
-            if j <= lines[i].len() - 4 {
-                // right or left
-                if lines[i][j..j + 4] == *"XMAS".as_bytes()
-                    || lines[i][j..j + 4] == *"SAMX".as_bytes()
-                {
-                    count += 1;
-                }
-                // up-right or down-left
-                if i >= 3
-                    && ((lines[i][j] == b'X'
-                        && lines[i - 1][j + 1] == b'M'
-                        && lines[i - 2][j + 2] == b'A'
-                        && lines[i - 3][j + 3] == b'S')
-                        || (lines[i][j] == b'S'
-                            && lines[i - 1][j + 1] == b'A'
-                            && lines[i - 2][j + 2] == b'M'
-                            && lines[i - 3][j + 3] == b'X'))
-                {
-                    count += 1;
-                }
-                // down-right or up-left
-                if i <= lines.len() - 4
-                    && ((lines[i][j] == b'X'
-                        && lines[i + 1][j + 1] == b'M'
-                        && lines[i + 2][j + 2] == b'A'
-                        && lines[i + 3][j + 3] == b'S')
-                        || (lines[i][j] == b'S'
-                            && lines[i + 1][j + 1] == b'A'
-                            && lines[i + 2][j + 2] == b'M'
-                            && lines[i + 3][j + 3] == b'X'))
-                {
-                    count += 1;
+/// (dx, dy) steps, clockwise from east.
+const DIRECTIONS: [(isize, isize); 8] = [
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+];
+
+fn cell(grid: &[Vec<u8>], x: isize, y: isize) -> Option<u8> {
+    let row = grid.get(usize::try_from(y).ok()?)?;
+    row.get(usize::try_from(x).ok()?).copied()
+}
+
+/// Where a word starts and which way it reads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct WordMatch {
+    x: usize,
+    y: usize,
+    direction: (isize, isize),
+}
+
+fn find_word(grid: &[Vec<u8>], word: &[u8]) -> Vec<WordMatch> {
+    let mut found = Vec::new();
+    for (y, row) in grid.iter().enumerate() {
+        for x in 0..row.len() {
+            for direction @ (dx, dy) in DIRECTIONS {
+                let reads = word.iter().enumerate().all(|(i, b)| {
+                    let i = i as isize;
+                    cell(grid, x as isize + (i * dx), y as isize + (i * dy)) == Some(*b)
+                });
+                if reads {
+                    found.push(WordMatch { x, y, direction });
                 }
             }
-            // down or up
-            if i <= lines.len() - 4
-                && ((lines[i][j] == b'X'
-                    && lines[i + 1][j] == b'M'
-                    && lines[i + 2][j] == b'A'
-                    && lines[i + 3][j] == b'S')
-                    || (lines[i][j] == b'S'
-                        && lines[i + 1][j] == b'A'
-                        && lines[i + 2][j] == b'M'
-                        && lines[i + 3][j] == b'X'))
-            {
-                count += 1;
+        }
+    }
+    found
+}
+
+/// A rectangular 2D pattern where `None` matches any character.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Mask {
+    rows: Vec<Vec<Option<u8>>>,
+}
+impl Mask {
+    fn parse(rows: &[&str], wildcard: u8) -> Self {
+        Mask {
+            rows: rows
+                .iter()
+                .map(|row| row.bytes().map(|b| (b != wildcard).then_some(b)).collect())
+                .collect(),
+        }
+    }
+
+    fn width(&self) -> usize {
+        self.rows.first().map_or(0, |row| row.len())
+    }
+
+    /// A quarter turn clockwise.
+    fn rotated(&self) -> Self {
+        let height = self.rows.len();
+        Mask {
+            rows: (0..self.width())
+                .map(|x| (0..height).rev().map(|y| self.rows[y][x]).collect())
+                .collect(),
+        }
+    }
+
+    /// Mirrored left to right.
+    fn reflected(&self) -> Self {
+        Mask {
+            rows: self
+                .rows
+                .iter()
+                .map(|row| row.iter().rev().copied().collect())
+                .collect(),
+        }
+    }
+
+    /// Every distinct way the mask can be turned or flipped, itself first.
+    fn orientations(&self) -> Vec<Mask> {
+        let mut all: Vec<Mask> = Vec::new();
+        let mut mask = self.clone();
+        for _ in 0..4 {
+            for candidate in [mask.clone(), mask.reflected()] {
+                if !all.contains(&candidate) {
+                    all.push(candidate);
+                }
             }
+            mask = mask.rotated();
         }
+        all
+    }
+
+    fn matches_at(&self, grid: &[Vec<u8>], x: usize, y: usize) -> bool {
+        self.rows.iter().enumerate().all(|(my, row)| {
+            row.iter().enumerate().all(|(mx, want)| match want {
+                None => cell(grid, (x + mx) as isize, (y + my) as isize).is_some(),
+                Some(b) => cell(grid, (x + mx) as isize, (y + my) as isize) == Some(*b),
+            })
+        })
     }
-    count
 }
 
-#[allow(dead_code)]
-fn count_x_mas_occurrences(filename: &str) -> u32 {
-    let lines = load_input(filename);
-    let mut count = 0;
-    for i in 1..lines.len() - 1 {
-        for j in 1..lines[i].len() - 1 {
-            // check A
-            if lines[i][j] == b'A'
-                // down down
-                && ((lines[i - 1][j - 1] == b'M'
-                    && lines[i - 1][j + 1] == b'M'
-                    && lines[i + 1][j - 1] == b'S'
-                    && lines[i + 1][j + 1] == b'S')
-                    // down up
-                    || (lines[i - 1][j - 1] == b'M'
-                        && lines[i - 1][j + 1] == b'S'
-                        && lines[i + 1][j - 1] == b'M'
-                        && lines[i + 1][j + 1] == b'S')
-                    // up down
-                    || (lines[i - 1][j - 1] == b'S'
-                        && lines[i - 1][j + 1] == b'M'
-                        && lines[i + 1][j - 1] == b'S'
-                        && lines[i + 1][j + 1] == b'M')
-                    // up up
-                    || (lines[i - 1][j - 1] == b'S'
-                        && lines[i - 1][j + 1] == b'S'
-                        && lines[i + 1][j - 1] == b'M'
-                        && lines[i + 1][j + 1] == b'M'))
-            {
-                count += 1;
+/// Top left corners where some orientation of `mask` fits, with the index of
+/// that orientation in `mask.orientations()`.
+fn find_mask(grid: &[Vec<u8>], mask: &Mask) -> Vec<(usize, usize, usize)> {
+    let orientations = mask.orientations();
+    let mut found = Vec::new();
+    for (y, row) in grid.iter().enumerate() {
+        for x in 0..row.len() {
+            for (i, oriented) in orientations.iter().enumerate() {
+                if oriented.matches_at(grid, x, y) {
+                    found.push((x, y, i));
+                }
             }
         }
     }
-    count
+    found
+}
+
+#[allow(dead_code)]
+fn count_xmas_occurrences(filename: &str) -> u32 {
+    let grid = load_input(filename);
+    find_word(&grid, b"XMAS").len() as u32
+}
+
+#[allow(dead_code)]
+fn count_x_mas_occurrences(filename: &str) -> u32 {
+    let grid = load_input(filename);
+    let x_mas = Mask::parse(&["M.S", ".A.", "M.S"], b'.');
+    find_mask(&grid, &x_mas).len() as u32
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{count_x_mas_occurrences, count_xmas_occurrences};
+    use super::{
+        count_x_mas_occurrences, count_xmas_occurrences, find_mask, find_word, Mask, WordMatch,
+    };
 
     #[test]
     fn part1_example() {
@@ -133,4 +179,51 @@ mod tests {
         let result = count_x_mas_occurrences("input.txt");
         assert_eq!(result, 1890);
     }
+
+    fn grid(rows: &[&str]) -> Vec<Vec<u8>> {
+        rows.iter().map(|row| row.bytes().collect()).collect()
+    }
+
+    #[test]
+    fn words_in_every_direction() {
+        let grid = grid(&["CAT.", "A..T", "T..A", ".TAC"]);
+        let found = find_word(&grid, b"CAT");
+        assert_eq!(
+            found,
+            [
+                WordMatch {
+                    x: 0,
+                    y: 0,
+                    direction: (1, 0)
+                },
+                WordMatch {
+                    x: 0,
+                    y: 0,
+                    direction: (0, 1)
+                },
+                WordMatch {
+                    x: 3,
+                    y: 3,
+                    direction: (-1, 0)
+                },
+                WordMatch {
+                    x: 3,
+                    y: 3,
+                    direction: (0, -1)
+                },
+            ]
+        );
+        assert_eq!(find_word(&grid, b"ATA").len(), 0);
+    }
+
+    #[test]
+    fn mask_orientations() {
+        let x_mas = Mask::parse(&["M.S", ".A.", "M.S"], b'.');
+        assert_eq!(x_mas.orientations().len(), 4);
+        let ell = Mask::parse(&["A.", "B.", "CD"], b'.');
+        assert_eq!(ell.orientations().len(), 8);
+        assert_eq!(ell.rotated().rows.len(), 2);
+        let grid = grid(&["xxDCx", "xxxBx", "xxxAx"]);
+        assert_eq!(find_mask(&grid, &ell).len(), 1);
+    }
 }
@@ -1,11 +1,56 @@
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeSet, HashMap, HashSet},
+    fmt,
     fs::File,
     io::{BufRead, BufReader},
 };
 
+use crate::util::validate::{validate, Check, Violation};
+
+type Input = (Vec<(u32, u32)>, Vec<Vec<u32>>);
+
+const INPUT_CHECKS: &[Check<Input>] = &[
+    Check {
+        assumption: "every page in an update appears in a rule",
+        test: |(rules, updates)| {
+            let ruled: HashSet<u32> = rules.iter().flat_map(|(a, b)| [*a, *b]).collect();
+            for (i, update) in updates.iter().enumerate() {
+                if let Some(page) = update.iter().find(|p| !ruled.contains(p)) {
+                    return Err(format!("page {page} in update {i}"));
+                }
+            }
+            Ok(())
+        },
+    },
+    // otherwise there's more than one valid order, or none
+    Check {
+        assumption: "rules order every pair of pages within an update",
+        test: |(rules, updates)| {
+            let rules: HashSet<(u32, u32)> = rules.iter().copied().collect();
+            for (i, update) in updates.iter().enumerate() {
+                for (j, a) in update.iter().enumerate() {
+                    if let Some(b) = update[j + 1..]
+                        .iter()
+                        .find(|b| !rules.contains(&(*a, **b)) && !rules.contains(&(**b, *a)))
+                    {
+                        return Err(format!("no rule between {a} and {b} in update {i}"));
+                    }
+                }
+            }
+            Ok(())
+        },
+    },
+    Check {
+        assumption: "every update has a middle page",
+        test: |(_, updates)| match updates.iter().position(|u| u.len() % 2 == 0) {
+            Some(i) => Err(format!("update {i} has {} pages", updates[i].len())),
+            None => Ok(()),
+        },
+    },
+];
+
 #[allow(dead_code, clippy::while_let_on_iterator)]
-fn parse_input(filename: &str) -> (Vec<(u32, u32)>, Vec<Vec<u32>>) {
+fn parse_input(filename: &str) -> Input {
     let file = File::open("input/2024/5/".to_owned() + filename).unwrap();
     let mut lines = BufReader::new(file).lines();
 
@@ -29,61 +74,239 @@ fn parse_input(filename: &str) -> (Vec<(u32, u32)>, Vec<Vec<u32>>) {
     (rules, updates)
 }
 
-fn construct_rule_order(rule_edges: &HashMap<u32, HashSet<u32>>, update: &[u32]) -> Vec<u32> {
-    let update_set: HashSet<u32> = update.iter().cloned().collect();
-    let mut pruned_rules: HashMap<u32, HashSet<u32>> = rule_edges
+/// `rule_edges[a]` holds every page that must come after `a`.
+fn rule_edges(rules: &[(u32, u32)]) -> HashMap<u32, HashSet<u32>> {
+    let mut edges: HashMap<u32, HashSet<u32>> = HashMap::new();
+    for &(before, after) in rules {
+        edges.entry(before).or_default().insert(after);
+        edges.entry(after).or_default();
+    }
+    edges
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct SortedUpdate {
+    pages: Vec<u32>,
+    // false if the rules leave some pair of pages free to swap
+    unique: bool,
+}
+
+/// Pages whose rules each require the next page to come later, the last
+/// requiring the first, so no order can satisfy them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct RuleCycle(Vec<u32>);
+impl fmt::Display for RuleCycle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for page in &self.0 {
+            write!(f, "{page}|")?;
+        }
+        write!(f, "{}", self.0[0])
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum OrderError {
+    Cycle(RuleCycle),
+    // a page listed twice can't sit in two places of one order
+    RepeatedPage(u32),
+}
+impl OrderError {
+    fn violation(&self, update: usize) -> Violation {
+        match self {
+            OrderError::Cycle(cycle) => Violation {
+                assumption: "rules never contradict each other within an update",
+                detail: format!("update {update} has the cycle {cycle}"),
+            },
+            OrderError::RepeatedPage(page) => Violation {
+                assumption: "no update lists a page twice",
+                detail: format!("update {update} has {page} more than once"),
+            },
+        }
+    }
+}
+
+/// Kahn's algorithm over the rules between the update's pages. Ties are broken
+/// by position in the update, so an already ordered update comes back as is.
+fn order_update(
+    rule_edges: &HashMap<u32, HashSet<u32>>,
+    update: &[u32],
+) -> Result<SortedUpdate, OrderError> {
+    let mut in_update = HashSet::new();
+    if let Some(page) = update.iter().find(|p| !in_update.insert(**p)) {
+        return Err(OrderError::RepeatedPage(*page));
+    }
+    let after = |page: u32| {
+        rule_edges
+            .get(&page)
+            .into_iter()
+            .flatten()
+            .copied()
+            .filter(|p| in_update.contains(p))
+    };
+    let mut blockers: HashMap<u32, usize> = update.iter().map(|p| (*p, 0)).collect();
+    for page in update {
+        for next in after(*page) {
+            *blockers.get_mut(&next).unwrap() += 1;
+        }
+    }
+
+    let mut ready: BTreeSet<(usize, u32)> = update
         .iter()
-        .filter(|(k, _)| update_set.contains(k))
-        .map(|(k, v)| {
-            let relevant_vals: HashSet<u32> = v.intersection(&update_set).cloned().collect();
-            (*k, relevant_vals)
-        })
+        .enumerate()
+        .filter(|(_, p)| blockers[p] == 0)
+        .map(|(i, p)| (i, *p))
         .collect();
+    let position: HashMap<u32, usize> = update.iter().enumerate().map(|(i, p)| (*p, i)).collect();
+    let mut pages = Vec::with_capacity(update.len());
+    let mut unique = true;
+    while let Some((_, page)) = ready.pop_first() {
+        unique &= ready.is_empty();
+        pages.push(page);
+        for next in after(page) {
+            let count = blockers.get_mut(&next).unwrap();
+            *count -= 1;
+            if *count == 0 {
+                ready.insert((position[&next], next));
+            }
+        }
+    }
+    if pages.len() == update.len() {
+        return Ok(SortedUpdate { pages, unique });
+    }
 
-    let mut order = Vec::new();
-    while !pruned_rules.is_empty() {
-        let next_val = pruned_rules
+    // every page left is still blocked by another page left, so walking
+    // backwards through blockers must eventually revisit one
+    let stuck: Vec<u32> = update.iter().copied().filter(|p| blockers[p] > 0).collect();
+    let blocked_by = |page: u32| {
+        *stuck
             .iter()
-            .find(|(_, v)| v.is_empty())
-            .map(|(k, _)| *k)
-            .unwrap();
-        pruned_rules.remove(&next_val);
-        pruned_rules.iter_mut().for_each(|(_, v)| {
-            v.remove(&next_val);
-        });
-        order.push(next_val);
-    }
-
-    order.reverse();
-    order
+            .find(|p| after(**p).any(|next| next == page))
+            .unwrap()
+    };
+    let mut walk = vec![stuck[0]];
+    loop {
+        let prev = blocked_by(*walk.last().unwrap());
+        if let Some(i) = walk.iter().position(|p| *p == prev) {
+            let mut cycle = walk.split_off(i);
+            cycle.reverse();
+            // start from whichever page the update lists first
+            let first = (0..cycle.len())
+                .min_by_key(|j| position[&cycle[*j]])
+                .unwrap();
+            cycle.rotate_left(first);
+            return Err(OrderError::Cycle(RuleCycle(cycle)));
+        }
+        walk.push(prev);
+    }
 }
 
 #[allow(dead_code)]
-fn updates_middles_sum(filename: &str, valids: bool) -> u32 {
-    let (rules, updates) = parse_input(filename);
+fn updates_middles_sum(filename: &str, valids: bool) -> Result<u32, Violation> {
+    let input = parse_input(filename);
+    validate(&input, INPUT_CHECKS)?;
+    let (rules, updates) = input;
+    let rule_edges = rule_edges(&rules);
 
-    let mut rule_edges = HashMap::new();
-    for rule in rules {
-        rule_edges
-            .entry(rule.0)
-            .and_modify(|v: &mut HashSet<u32>| {
-                v.insert(rule.1);
-            })
-            .or_insert(HashSet::from([rule.1]));
-        rule_edges.entry(rule.1).or_insert(HashSet::new());
-    }
-
-    // for each update, check if all pre-reqs have been seen at each number
     let mut middles_sum = 0;
-    for update in updates {
-        let rule_based_order = construct_rule_order(&rule_edges, &update);
-        if valids && rule_based_order == update {
+    for (i, update) in updates.iter().enumerate() {
+        let sorted = order_update(&rule_edges, update).map_err(|err| err.violation(i))?;
+        if valids && sorted.pages == *update {
             middles_sum += update[update.len() / 2];
-        } else if !valids && rule_based_order != update {
-            middles_sum += rule_based_order[rule_based_order.len() / 2];
+        } else if !valids && sorted.pages != *update {
+            middles_sum += sorted.pages[sorted.pages.len() / 2];
+        }
+    }
+    Ok(middles_sum)
+}
+
+/// Swaps the pages at `at` and `at + 1`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Swap {
+    at: usize,
+    pages: (u32, u32),
+}
+
+/// What it takes to fix one update that breaks the rules.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Correction {
+    update: usize,
+    // each as the rule `before|after` that the update has the wrong way round
+    violated: Vec<(u32, u32)>,
+    // the inversion count against the rule order, which no shorter fix beats
+    min_swaps: usize,
+    swaps: Vec<Swap>,
+}
+
+fn correct_update(
+    rule_edges: &HashMap<u32, HashSet<u32>>,
+    index: usize,
+    update: &[u32],
+) -> Result<Option<Correction>, OrderError> {
+    let sorted = order_update(rule_edges, update)?;
+    if sorted.pages == update {
+        return Ok(None);
+    }
+    let must_precede = |a: u32, b: u32| rule_edges.get(&a).is_some_and(|after| after.contains(&b));
+
+    let mut violated = Vec::new();
+    for (i, a) in update.iter().enumerate() {
+        for b in &update[i + 1..] {
+            if must_precede(*b, *a) {
+                violated.push((*b, *a));
+            }
+        }
+    }
+
+    let rank: HashMap<u32, usize> = sorted
+        .pages
+        .iter()
+        .enumerate()
+        .map(|(i, p)| (*p, i))
+        .collect();
+    let mut ranks: Vec<usize> = update.iter().map(|p| rank[p]).collect();
+    let min_swaps = (0..ranks.len())
+        .map(|i| ranks[i + 1..].iter().filter(|r| **r < ranks[i]).count())
+        .sum();
+
+    // bubble sort only ever swaps an inverted pair, so it takes exactly
+    // `min_swaps` steps
+    let mut pages = update.to_vec();
+    let mut swaps = Vec::new();
+    for end in (1..ranks.len()).rev() {
+        for at in 0..end {
+            if ranks[at] > ranks[at + 1] {
+                swaps.push(Swap {
+                    at,
+                    pages: (pages[at], pages[at + 1]),
+                });
+                ranks.swap(at, at + 1);
+                pages.swap(at, at + 1);
+            }
         }
     }
-    middles_sum
+
+    Ok(Some(Correction {
+        update: index,
+        violated,
+        min_swaps,
+        swaps,
+    }))
+}
+
+/// One correction per update that isn't already in rule order.
+#[allow(dead_code)]
+fn correction_report(filename: &str) -> Result<Vec<Correction>, Violation> {
+    let input = parse_input(filename);
+    validate(&input, INPUT_CHECKS)?;
+    let (rules, updates) = input;
+    let rule_edges = rule_edges(&rules);
+
+    let mut report = Vec::new();
+    for (i, update) in updates.iter().enumerate() {
+        let correction = correct_update(&rule_edges, i, update).map_err(|err| err.violation(i))?;
+        report.extend(correction);
+    }
+    Ok(report)
 }
 
 // correct traversal of the rule graph will provide an ordering.
@@ -98,17 +321,22 @@ fn updates_middles_sum(filename: &str, valids: bool) -> u32 {
 mod tests {
     use std::collections::HashSet;
 
-    use super::{parse_input, updates_middles_sum};
+    use crate::util::validate::validate;
+
+    use super::{
+        correction_report, order_update, parse_input, rule_edges, updates_middles_sum, OrderError,
+        RuleCycle, Swap, INPUT_CHECKS,
+    };
 
     #[test]
     fn part1_example() {
-        let result = updates_middles_sum("example.txt", true);
+        let result = updates_middles_sum("example.txt", true).unwrap();
         assert_eq!(result, 143);
     }
 
     #[test]
     fn part1() {
-        let result = updates_middles_sum("input.txt", true);
+        let result = updates_middles_sum("input.txt", true).unwrap();
         assert_eq!(result, 5391);
     }
 
@@ -132,13 +360,72 @@ mod tests {
 
     #[test]
     fn part2_example() {
-        let result = updates_middles_sum("example.txt", false);
+        let result = updates_middles_sum("example.txt", false).unwrap();
         assert_eq!(result, 123);
     }
 
     #[test]
     fn part2() {
-        let result = updates_middles_sum("input.txt", false);
+        let result = updates_middles_sum("input.txt", false).unwrap();
         assert_eq!(result, 6142);
     }
+
+    #[test]
+    fn rejects_unordered_pages() {
+        let rules = vec![(1, 2), (2, 3)];
+        let violation = validate(&(rules.clone(), vec![vec![1, 2, 4]]), INPUT_CHECKS).unwrap_err();
+        assert_eq!(violation.detail, "page 4 in update 0");
+        let violation = validate(&(rules.clone(), vec![vec![1, 2, 3]]), INPUT_CHECKS).unwrap_err();
+        assert_eq!(violation.detail, "no rule between 1 and 3 in update 0");
+        let violation = validate(&(rules, vec![vec![1, 2]]), INPUT_CHECKS).unwrap_err();
+        assert_eq!(violation.assumption, "every update has a middle page");
+    }
+
+    #[test]
+    fn orders_or_finds_cycle() {
+        let edges = rule_edges(&[(1, 2), (2, 3), (1, 3)]);
+        let sorted = order_update(&edges, &[3, 1, 2]).unwrap();
+        assert_eq!(sorted.pages, [1, 2, 3]);
+        assert!(sorted.unique);
+        // nothing orders 4 against the others
+        let sorted = order_update(&edges, &[4, 3, 1, 2]).unwrap();
+        assert_eq!(sorted.pages, [4, 1, 2, 3]);
+        assert!(!sorted.unique);
+
+        let edges = rule_edges(&[(1, 2), (2, 3), (3, 4), (4, 2), (5, 1)]);
+        let cycle = order_update(&edges, &[5, 1, 2, 3, 4]).unwrap_err();
+        assert_eq!(cycle, OrderError::Cycle(RuleCycle(vec![2, 3, 4])));
+        assert_eq!(cycle.violation(0).detail, "update 0 has the cycle 2|3|4|2");
+    }
+
+    #[test]
+    fn repeated_page() {
+        let edges = rule_edges(&[(1, 2), (2, 3)]);
+        let err = order_update(&edges, &[1, 2, 1, 3]).unwrap_err();
+        assert_eq!(err, OrderError::RepeatedPage(1));
+        assert_eq!(err.violation(2).assumption, "no update lists a page twice");
+    }
+
+    #[test]
+    fn corrections_example() {
+        let report = correction_report("example.txt").unwrap();
+        assert_eq!(
+            report.iter().map(|c| c.update).collect::<Vec<_>>(),
+            [3, 4, 5]
+        );
+        // 61,13,29 needs 29|13 fixed
+        assert_eq!(report[1].violated, [(29, 13)]);
+        assert_eq!(report[1].min_swaps, 1);
+        assert_eq!(
+            report[1].swaps,
+            [Swap {
+                at: 1,
+                pages: (13, 29)
+            }]
+        );
+        for correction in &report {
+            assert_eq!(correction.swaps.len(), correction.min_swaps);
+            assert_eq!(correction.violated.len(), correction.min_swaps);
+        }
+    }
 }
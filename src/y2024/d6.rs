@@ -1,218 +1,295 @@
 #![allow(dead_code)]
 use std::{
-    collections::HashSet,
+    collections::{BTreeSet, HashSet},
     fs::File,
     io::{BufRead, BufReader},
-    thread,
+};
+
+use crate::util::{
+    bitgrid::BitGrid,
+    parallel::Parallel,
+    term::{Cell, Color, Frame, Style},
 };
 
 type Position = (usize, usize);
 
+// (row, col) steps for up, right, down, left; turning right moves to the next one
+const HEADINGS: [(isize, isize); 4] = [(-1, 0), (0, 1), (1, 0), (0, -1)];
+// how a guard facing each of those headings is drawn
+const GUARD_SYMBOLS: [u8; 4] = *b"^>v<";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Guard {
+    pos: Position,
+    heading: usize,
+}
+
 #[derive(Clone)]
 struct Map {
-    map: Vec<Vec<u8>>,
+    obstacles: BitGrid,
+    // one layer per heading, marking cells the guard has entered facing that way
+    visited: [BitGrid; 4],
     width: usize,
     height: usize,
 }
 impl Map {
-    fn new(map: Vec<Vec<u8>>) -> Self {
-        let height = map.len();
-        let width = map[0].len();
-        Map { map, width, height }
+    fn new(obstacles: BitGrid) -> Self {
+        let width = obstacles.width();
+        let height = obstacles.height();
+        let empty = BitGrid::new(width, height);
+        Map {
+            obstacles,
+            visited: [empty.clone(), empty.clone(), empty.clone(), empty],
+            width,
+            height,
+        }
     }
 
     fn add_obstacle(&mut self, (row, col): Position) {
-        self.map[row][col] = b'#';
+        self.obstacles.set(col, row);
     }
 
     fn remove_obstacle(&mut self, (row, col): Position) {
-        self.map[row][col] = 0;
+        self.obstacles.clear(col, row);
+    }
+
+    fn clear_visited(&mut self) {
+        for layer in self.visited.iter_mut() {
+            layer.clear_all();
+        }
+    }
+
+    fn next_position(&self, (row, col): Position, heading: usize) -> Option<Position> {
+        let (dr, dc) = HEADINGS[heading];
+        let row = row.checked_add_signed(dr)?;
+        let col = col.checked_add_signed(dc)?;
+        (row < self.height && col < self.width).then_some((row, col))
     }
 }
 
-fn get_input(filename: &str) -> (Map, Position) {
+fn get_input(filename: &str) -> (Map, Vec<Guard>) {
     let file = File::open("input/2024/6/".to_owned() + filename).unwrap();
-
-    let mut map: Vec<Vec<u8>> = BufReader::new(file)
+    let lines: Vec<Vec<u8>> = BufReader::new(file)
         .lines()
-        .map(|line| {
-            line.unwrap()
-                .bytes()
-                .map(|b| if b == b'.' { 0 } else { b })
-                .collect()
-        })
+        .map(|line| line.unwrap().into_bytes())
         .collect();
-    for r in 0..map.len() {
-        for c in 0..map[r].len() {
-            if map[r][c] == b'^' {
-                map[r][c] = 0;
-                return (Map::new(map), (r, c));
+
+    let mut obstacles = BitGrid::new(lines[0].len(), lines.len());
+    let mut guards = Vec::new();
+    for (r, line) in lines.iter().enumerate() {
+        for (c, b) in line.iter().enumerate() {
+            if *b == b'#' {
+                obstacles.set(c, r);
+            } else if let Some(heading) = GUARD_SYMBOLS.iter().position(|g| g == b) {
+                guards.push(Guard {
+                    pos: (r, c),
+                    heading,
+                });
             }
         }
     }
-    unreachable!();
+    assert!(!guards.is_empty(), "no guard in {filename}");
+    (Map::new(obstacles), guards)
 }
 
+/// Cells covered by any guard. Guards walk independently of each other.
 fn count_distinct_positions(filename: &str) -> usize {
-    let (mut map_data, starting_pos) = get_input(filename);
-    let visited = positions_visited(&mut map_data, starting_pos).unwrap();
-    let unique: HashSet<_> = visited.iter().collect();
-    unique.len()
-}
-
-fn clear_visited(map: &mut Map, visited: impl Iterator<Item = Position>) {
-    for (row, col) in visited {
-        map.map[row][col] = 0;
+    let (mut map_data, guards) = get_input(filename);
+    let mut unique = HashSet::new();
+    for guard in guards {
+        // a guard already stuck in a loop still covers every cell of it
+        unique.extend(positions_visited(&mut map_data, guard).unwrap_or_else(|cells| cells));
+        map_data.clear_visited();
     }
+    unique.len()
 }
 
-fn count_loopable_obstacle_insertions(filename: &str, num_threads: usize) -> usize {
-    let (mut map, starting_pos) = get_input(filename);
-    let visited = positions_visited(&mut map, starting_pos).unwrap();
-    let visited: HashSet<_> = visited.into_iter().collect();
-    let visited: Vec<Position> = visited.into_iter().collect();
-    clear_visited(&mut map, visited.iter().copied());
-    let mut children = Vec::new();
-
-    let mut chunk_size = visited.len() / num_threads;
-    if visited.len() % chunk_size != 0 {
-        chunk_size += 1;
-    }
-    let mut visited_chunks = visited.chunks(chunk_size);
-    for _ in 0..num_threads {
-        let mut map = map.clone();
-        let positions: Vec<Position> = visited_chunks.next().unwrap().to_vec();
-        let child = thread::spawn(move || {
-            let mut count = 0;
-            for obs_pos in positions {
-                if starting_pos == obs_pos {
-                    continue;
-                }
-                map.add_obstacle(obs_pos);
-                match positions_visited(&mut map, starting_pos) {
-                    Ok(attempt_visits) => clear_visited(&mut map, attempt_visits.iter().copied()),
-                    Err(attempt_visits) => {
-                        count += 1;
-                        clear_visited(&mut map, attempt_visits.iter().copied());
-                    }
-                }
-                map.remove_obstacle(obs_pos);
-            }
-            count
-        });
-        children.push(child);
-    }
-
-    children
-        .into_iter()
-        .map(|child| child.join().unwrap())
-        .sum()
-}
-
-#[inline]
-fn mark_up(pos: u8) -> u8 {
-    assert_ne!(pos, b'#');
-    pos | 0b1
+fn count_loopable_obstacle_insertions(filename: &str) -> usize {
+    let (mut map, guards) = get_input(filename);
+    loop_obstacles(&mut map, &guards).len()
 }
 
-#[inline]
-fn already_up(pos: u8) -> bool {
-    pos & 0b1 == 0b1
-}
+/// Every cell where one new obstacle traps at least one of the guards in a
+/// loop. Obstacles can't go where a guard is standing, and a guard that loops
+/// without any new obstacle is skipped, since no obstacle causes its loop.
+fn loop_obstacles(map: &mut Map, guards: &[Guard]) -> BTreeSet<Position> {
+    let jumps = JumpTable::new(map);
+    let mut found = BTreeSet::new();
+    for guard in guards {
+        // only an obstacle in the guard's way can change the route
+        let visited = positions_visited(map, *guard);
+        map.clear_visited();
+        let Ok(visited) = visited else {
+            continue;
+        };
+        let visited: HashSet<_> = visited.into_iter().collect();
+        let mut visited: Vec<Position> = visited.into_iter().collect();
+        visited.retain(|pos| !guards.iter().any(|g| g.pos == *pos));
 
-#[inline]
-fn mark_right(pos: u8) -> u8 {
-    assert_ne!(pos, b'#');
-    pos | 0b10
+        let loops = Parallel::new().map_with(
+            &visited,
+            || map.visited.clone(),
+            |turns, obs_pos| jumps.loops_with_obstacle(turns, *guard, *obs_pos),
+        );
+        found.extend(
+            visited
+                .into_iter()
+                .zip(loops)
+                .filter(|(_, looped)| *looped)
+                .map(|(pos, _)| pos),
+        );
+    }
+    found
 }
 
-#[inline]
-fn already_right(pos: u8) -> bool {
-    pos & 0b10 == 0b10
+/// For every cell and heading, where the guard stops in front of the next
+/// obstacle, or `None` if the guard walks off the map first. Lets a walk skip
+/// straight from turn to turn.
+struct JumpTable {
+    width: usize,
+    stops: [Vec<Option<Position>>; 4],
 }
+impl JumpTable {
+    fn new(map: &Map) -> Self {
+        let cells = map.width * map.height;
+        let mut stops = [
+            vec![None; cells],
+            vec![None; cells],
+            vec![None; cells],
+            vec![None; cells],
+        ];
+        for (heading, stops) in stops.iter_mut().enumerate() {
+            // fill each cell after the one it steps into
+            let (dr, dc) = HEADINGS[heading];
+            let rows: Vec<usize> = match dr {
+                1 => (0..map.height).rev().collect(),
+                _ => (0..map.height).collect(),
+            };
+            let cols: Vec<usize> = match dc {
+                1 => (0..map.width).rev().collect(),
+                _ => (0..map.width).collect(),
+            };
+            for &row in &rows {
+                for &col in &cols {
+                    stops[row * map.width + col] = match map.next_position((row, col), heading) {
+                        None => None,
+                        Some((r, c)) if map.obstacles.get(c, r) => Some((row, col)),
+                        Some((r, c)) => stops[r * map.width + c],
+                    };
+                }
+            }
+        }
+        JumpTable {
+            width: map.width,
+            stops,
+        }
+    }
 
-#[inline]
-fn mark_down(pos: u8) -> u8 {
-    assert_ne!(pos, b'#');
-    pos | 0b100
-}
+    /// The stop from `pos`, taking one extra obstacle into account.
+    fn stop(&self, (row, col): Position, heading: usize, extra: Position) -> Option<Position> {
+        let stop = self.stops[heading][row * self.width + col];
+        let (dr, dc) = HEADINGS[heading];
+        let ahead = match (dr, dc) {
+            (_, 0) if extra.1 == col => (extra.0 as isize - row as isize) * dr,
+            (0, _) if extra.0 == row => (extra.1 as isize - col as isize) * dc,
+            _ => 0,
+        };
+        let reach = stop.map_or(isize::MAX, |(r, c)| {
+            r.abs_diff(row).max(c.abs_diff(col)) as isize
+        });
+        if ahead >= 1 && ahead <= reach {
+            let steps = ahead - 1;
+            return Some((
+                row.wrapping_add_signed(dr * steps),
+                col.wrapping_add_signed(dc * steps),
+            ));
+        }
+        stop
+    }
 
-#[inline]
-fn already_down(pos: u8) -> bool {
-    pos & 0b100 == 0b100
+    /// Like `positions_visited(..).is_err()` with `obstacle` added, but only
+    /// visits the cells where the guard turns. `turns` must start empty and
+    /// is left empty.
+    fn loops_with_obstacle(
+        &self,
+        turns: &mut [BitGrid; 4],
+        guard: Guard,
+        obstacle: Position,
+    ) -> bool {
+        let Guard {
+            mut pos,
+            mut heading,
+        } = guard;
+        let mut marked = Vec::new();
+        let mut looped = false;
+        while let Some(stop) = self.stop(pos, heading, obstacle) {
+            if turns[heading].test_and_set(stop.1, stop.0) {
+                looped = true;
+                break;
+            }
+            marked.push((stop, heading));
+            pos = stop;
+            heading = (heading + 1) % 4;
+        }
+        for ((row, col), heading) in marked {
+            turns[heading].clear(col, row);
+        }
+        looped
+    }
 }
 
-#[inline]
-fn mark_left(pos: u8) -> u8 {
-    assert_ne!(pos, b'#');
-    pos | 0b1000
+fn render_path(map: &Map, positions: &[Position]) -> Frame {
+    let mut frame = Frame::from_fn(map.width, map.height, |x, y| {
+        Cell::plain(if map.obstacles.get(x, y) { '#' } else { '.' })
+    });
+    frame.mark(
+        positions.iter().map(|(row, col)| (*col, *row)),
+        Cell::styled('X', Style::fg(Color::Cyan)),
+    );
+    frame
 }
 
-#[inline]
-fn already_left(pos: u8) -> bool {
-    pos & 0b1000 == 0b1000
+// Err means the guard ended up in a loop
+fn positions_visited(map: &mut Map, guard: Guard) -> Result<Vec<Position>, Vec<Position>> {
+    let cells = |path: Vec<Guard>| {
+        let mut cells: Vec<Position> = path.into_iter().map(|g| g.pos).collect();
+        // turning on the spot isn't a new position
+        cells.dedup();
+        cells
+    };
+    guard_path(map, guard).map(cells).map_err(cells)
 }
 
-fn positions_visited(map: &mut Map, mut pos: Position) -> Result<Vec<Position>, Vec<Position>> {
-    let mut positions = Vec::new();
-    map.map[pos.0][pos.1] = mark_up(map.map[pos.0][pos.1]);
-    positions.push(pos);
-    loop {
-        // going up
-        while pos.0 > 0 && map.map[pos.0 - 1][pos.1] != b'#' {
-            pos = (pos.0 - 1, pos.1);
-            if already_up(map.map[pos.0][pos.1]) {
-                return Err(positions);
-            }
-            map.map[pos.0][pos.1] = mark_up(map.map[pos.0][pos.1]);
-            positions.push(pos);
-        }
-        if pos.0 == 0 {
-            break;
-        }
-        // going right
-        while pos.1 < map.width - 1 && map.map[pos.0][pos.1 + 1] != b'#' {
-            pos = (pos.0, pos.1 + 1);
-            if already_right(map.map[pos.0][pos.1]) {
-                return Err(positions);
-            }
-            map.map[pos.0][pos.1] = mark_right(map.map[pos.0][pos.1]);
-            positions.push(pos);
-        }
-        if pos.1 == map.width - 1 {
-            break;
-        }
-        // going down
-        while pos.0 < map.height - 1 && map.map[pos.0 + 1][pos.1] != b'#' {
-            pos = (pos.0 + 1, pos.1);
-            if already_down(map.map[pos.0][pos.1]) {
-                return Err(positions);
-            }
-            map.map[pos.0][pos.1] = mark_down(map.map[pos.0][pos.1]);
-            positions.push(pos);
+/// Every step the guard takes in order, a turn on the spot counting as a step.
+/// Err means the guard ended up in a loop, with the path up to where it first
+/// repeats itself.
+fn guard_path(map: &mut Map, mut guard: Guard) -> Result<Vec<Guard>, Vec<Guard>> {
+    let mut path = vec![guard];
+    map.visited[guard.heading].set(guard.pos.1, guard.pos.0);
+    while let Some(next) = map.next_position(guard.pos, guard.heading) {
+        if map.obstacles.get(next.1, next.0) {
+            guard.heading = (guard.heading + 1) % 4;
+        } else {
+            guard.pos = next;
         }
-        if pos.0 == map.height - 1 {
-            break;
-        }
-        // going left
-        while pos.1 > 0 && map.map[pos.0][pos.1 - 1] != b'#' {
-            pos = (pos.0, pos.1 - 1);
-            if already_left(map.map[pos.0][pos.1]) {
-                return Err(positions);
-            }
-            map.map[pos.0][pos.1] = mark_left(map.map[pos.0][pos.1]);
-            positions.push(pos);
-        }
-        if pos.1 == 0 {
-            break;
+        if map.visited[guard.heading].test_and_set(guard.pos.1, guard.pos.0) {
+            return Err(path);
         }
+        path.push(guard);
     }
-    Ok(positions)
+    Ok(path)
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{count_distinct_positions, count_loopable_obstacle_insertions};
+    use std::collections::BTreeSet;
+
+    use crate::util::snapshot::assert_snapshot;
+
+    use super::{
+        count_distinct_positions, count_loopable_obstacle_insertions, get_input, guard_path,
+        loop_obstacles, positions_visited, render_path, Guard, JumpTable,
+    };
 
     #[test]
     fn part1_example() {
@@ -228,13 +305,95 @@ mod tests {
 
     #[test]
     fn part2_example() {
-        let result = count_loopable_obstacle_insertions("example.txt", 1);
+        let result = count_loopable_obstacle_insertions("example.txt");
         assert_eq!(result, 6);
     }
 
     #[test]
     fn part2() {
-        let result = count_loopable_obstacle_insertions("input.txt", 8);
+        let result = count_loopable_obstacle_insertions("input.txt");
         assert_eq!(result, 1562);
     }
+
+    #[test]
+    fn part1_example_path() {
+        let (mut map, guards) = get_input("example.txt");
+        let path = positions_visited(&mut map, guards[0]).unwrap();
+        assert_snapshot(
+            "2024/6/part1_example",
+            &render_path(&map, &path).to_plain_string(),
+        );
+    }
+
+    #[test]
+    fn jumps_match_walking() {
+        let (mut map, guards) = get_input("example.txt");
+        let start = guards[0];
+        let jumps = JumpTable::new(&map);
+        let mut turns = map.visited.clone();
+        for row in 0..map.height {
+            for col in 0..map.width {
+                if map.obstacles.get(col, row) || (row, col) == start.pos {
+                    continue;
+                }
+                map.add_obstacle((row, col));
+                let walked = positions_visited(&mut map, start).is_err();
+                map.clear_visited();
+                map.remove_obstacle((row, col));
+                let jumped = jumps.loops_with_obstacle(&mut turns, start, (row, col));
+                assert_eq!(jumped, walked, "obstacle at {:?}", (row, col));
+            }
+        }
+        assert_eq!(turns.iter().map(|t| t.count_ones()).sum::<usize>(), 0);
+    }
+
+    #[test]
+    fn example_loop_obstacles() {
+        let (mut map, guards) = get_input("example.txt");
+        let found = loop_obstacles(&mut map, &guards);
+        assert_eq!(
+            found.into_iter().collect::<Vec<_>>(),
+            [(6, 3), (7, 6), (7, 7), (8, 1), (8, 3), (9, 7)]
+        );
+    }
+
+    #[test]
+    fn path_with_headings() {
+        let (mut map, _) = get_input("example.txt");
+        // facing the obstacle at (1, 9), so turns right and steps off the map
+        let guard = Guard {
+            pos: (2, 9),
+            heading: 0,
+        };
+        let path = guard_path(&mut map, guard).unwrap();
+        assert_eq!(
+            path,
+            [
+                guard,
+                Guard {
+                    pos: (2, 9),
+                    heading: 1
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn several_guards() {
+        let (_, guards) = get_input("example_guards.txt");
+        assert_eq!(guards.iter().map(|g| g.heading).collect::<Vec<_>>(), [3, 0]);
+        // the `<` guard walks row 2 straight off the map, adding 8 new cells
+        assert_eq!(count_distinct_positions("example_guards.txt"), 49);
+        assert_eq!(count_loopable_obstacle_insertions("example_guards.txt"), 6);
+    }
+
+    #[test]
+    fn guard_looping_from_start() {
+        let (mut map, guards) = get_input("example_stuck.txt");
+        assert!(guard_path(&mut map, guards[0]).is_err());
+        map.clear_visited();
+        // the looping guard circles 6 cells, the other steps straight off the map
+        assert_eq!(count_distinct_positions("example_stuck.txt"), 7);
+        assert_eq!(loop_obstacles(&mut map, &guards), BTreeSet::new());
+    }
 }
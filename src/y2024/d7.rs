@@ -5,6 +5,11 @@ use std::{
     io::{BufRead, BufReader},
 };
 
+use crate::util::{
+    parallel::Parallel,
+    search::{Search, SearchState, Strategy},
+};
+
 fn get_input(filename: &str) -> Vec<(u64, Vec<u64>)> {
     let file = File::open("input/2024/7/".to_owned() + filename).unwrap();
     BufReader::new(file)
@@ -24,36 +29,75 @@ fn get_input(filename: &str) -> Vec<(u64, Vec<u64>)> {
         .collect()
 }
 
-fn can_achieve_total(values: &[u64], goal: u64, allow_concat: bool, so_far: u64) -> bool {
-    if values.is_empty() {
-        return so_far == goal;
-    }
-    if so_far > goal {
-        return false;
+/// The running total after combining the first `idx` values.
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct Partial<'a> {
+    values: &'a [u64],
+    goal: u64,
+    allow_concat: bool,
+    idx: usize,
+    so_far: u64,
+}
+impl SearchState for Partial<'_> {
+    fn is_goal(&self) -> bool {
+        self.idx == self.values.len() && self.so_far == self.goal
     }
-    // special case that will only happen at beginning
-    if so_far == 0 {
-        return can_achieve_total(&values[1..], goal, allow_concat, values[0]);
+
+    fn successors(&self, out: &mut Vec<Self>) {
+        let Some(&value) = self.values.get(self.idx) else {
+            return;
+        };
+        let next = |so_far| Partial {
+            idx: self.idx + 1,
+            so_far,
+            ..*self
+        };
+        if self.idx == 0 {
+            out.push(next(value));
+            return;
+        }
+        out.push(next(self.so_far * value));
+        out.push(next(self.so_far + value));
+        if self.allow_concat {
+            out.push(next(format!("{}{value}", self.so_far).parse().unwrap()));
+        }
     }
-    if can_achieve_total(&values[1..], goal, allow_concat, so_far * values[0])
-        || can_achieve_total(&values[1..], goal, allow_concat, so_far + values[0])
-    {
-        true
-    } else if allow_concat {
-        let concatenated = format!("{so_far}{}", values[0]).parse::<u64>().unwrap();
-        can_achieve_total(&values[1..], goal, allow_concat, concatenated)
-    } else {
-        false
+
+    // every operator only makes the total bigger
+    fn prune(&self) -> bool {
+        self.so_far > self.goal
     }
 }
 
+fn can_achieve_total(values: &[u64], goal: u64, allow_concat: bool) -> bool {
+    let root = Partial {
+        values,
+        goal,
+        allow_concat,
+        idx: 0,
+        so_far: 0,
+    };
+    Search::new(Strategy::DepthFirst)
+        .run(root)
+        .solution
+        .is_some()
+}
+
 fn sum_functions_that_work(filename: &str, allow_concat: bool) -> u64 {
     let functions = get_input(filename);
-    functions
-        .iter()
-        .filter(|(total, values)| can_achieve_total(values, *total, allow_concat, 0))
-        .map(|(total, _)| total)
-        .sum()
+    Parallel::new()
+        .map_reduce(
+            &functions,
+            |(total, values)| {
+                if can_achieve_total(values, *total, allow_concat) {
+                    *total
+                } else {
+                    0
+                }
+            },
+            |a, b| a + b,
+        )
+        .unwrap_or(0)
 }
 
 #[cfg(test)]
@@ -1,14 +1,41 @@
 #![allow(dead_code)]
 use std::fs;
 
-fn load_input(filename: &str) -> Vec<u8> {
+use crate::util::{
+    interval::IntervalSet,
+    validate::{validate, Check, Violation},
+};
+
+const INPUT_CHECKS: &[Check<str>] = &[
+    Check {
+        assumption: "disk map is all digits",
+        test: |map| match map.char_indices().find(|(_, c)| !c.is_ascii_digit()) {
+            Some((i, c)) => Err(format!("{c:?} at offset {i}")),
+            None => Ok(()),
+        },
+    },
+    // both parts walk files from the right, and expect the last entry to be one
+    Check {
+        assumption: "disk map ends with a file",
+        test: |map| {
+            if map.len() % 2 == 1 {
+                Ok(())
+            } else {
+                Err(format!("{} entries, ending with free space", map.len()))
+            }
+        },
+    },
+];
+
+fn load_input(filename: &str) -> Result<Vec<u8>, Violation> {
     let contents = fs::read_to_string("input/2024/9/".to_owned() + filename).unwrap();
-    contents.trim().bytes().map(|b| b - b'0').collect()
+    let map = contents.trim();
+    validate(map, INPUT_CHECKS)?;
+    Ok(map.bytes().map(|b| b - b'0').collect())
 }
 
-fn checksum_after_moving_blocks(filename: &str) -> usize {
-    let mut disk = load_input(filename);
-    assert!((disk.len() - 1) % 2 == 0);
+fn checksum_after_moving_blocks(filename: &str) -> Result<usize, Violation> {
+    let mut disk = load_input(filename)?;
     let mut left = 0;
     let mut block_position: usize = 0;
     let mut right = disk.len() - 1;
@@ -34,91 +61,74 @@ fn checksum_after_moving_blocks(filename: &str) -> usize {
         block_position += 1;
         disk[left] -= 1;
     }
-    checksum
+    Ok(checksum)
 }
 
-fn checksum_after_moving_files(filename: &str) -> usize {
-    let disk = load_input(filename);
+fn checksum_after_moving_files(filename: &str) -> Result<usize, Violation> {
+    let disk = load_input(filename)?;
     let mut block_position = 0;
     let mut files = Vec::with_capacity(disk.len() / 2);
-    let mut free_space: [Vec<usize>; 10] = [
-        Vec::new(),
-        Vec::new(),
-        Vec::new(),
-        Vec::new(),
-        Vec::new(),
-        Vec::new(),
-        Vec::new(),
-        Vec::new(),
-        Vec::new(),
-        Vec::new(),
-    ];
+    let mut free_space = IntervalSet::new();
     for (idx, space) in disk.iter().enumerate() {
         let space = *space as usize;
         if idx % 2 == 0 {
             files.push((idx / 2, block_position..block_position + space));
-        } else if space != 0 {
-            free_space[space].push(block_position);
+        } else {
+            free_space.insert(block_position..block_position + space);
         }
         block_position += space;
     }
-    for fs in free_space.iter_mut() {
-        fs.reverse();
-    }
 
     let mut checksum = 0;
-    while let Some(file) = files.pop() {
-        let file_size = file.1.len();
-        if let Some(i) = free_space
-            .iter()
-            .enumerate()
-            .flat_map(|(idx, spaces)| spaces.last().map(|x| (idx, x)))
-            .filter(|(idx, pos)| *idx >= file_size && **pos < file.1.start)
-            .min_by_key(|(_, pos)| **pos)
-            .map(|(idx, _)| idx)
-        {
-            let pos = free_space[i].pop().unwrap();
-            checksum += (pos..pos + file_size).map(|x| x * file.0).sum::<usize>();
-            if i != file_size {
-                let new_space_size = i - file_size;
-                let new_pos = pos + file_size;
-                let insert_idx = free_space[new_space_size]
-                    .binary_search_by(|x| x.cmp(&new_pos).reverse())
-                    .unwrap_err();
-                free_space[new_space_size].insert(insert_idx, new_pos);
+    while let Some((file_id, file)) = files.pop() {
+        let dest = match free_space.first_fit_below(file.len(), file.start) {
+            Some(dest) => {
+                free_space.remove(dest.clone());
+                free_space.insert(file);
+                dest
             }
-        } else {
-            checksum += file.1.map(|x| x * file.0).sum::<usize>();
-        }
+            None => file,
+        };
+        checksum += dest.map(|x| x * file_id).sum::<usize>();
     }
-    checksum
+    Ok(checksum)
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{checksum_after_moving_blocks, checksum_after_moving_files};
+    use crate::util::validate::validate;
+
+    use super::{checksum_after_moving_blocks, checksum_after_moving_files, INPUT_CHECKS};
 
     #[test]
     fn part1_example() {
-        let result = checksum_after_moving_blocks("example.txt");
+        let result = checksum_after_moving_blocks("example.txt").unwrap();
         assert_eq!(result, 1928);
     }
 
     #[test]
     fn part1() {
-        let result = checksum_after_moving_blocks("input.txt");
+        let result = checksum_after_moving_blocks("input.txt").unwrap();
         assert_eq!(result, 6154342787400);
     }
 
     #[test]
     fn part2_example() {
-        let result = checksum_after_moving_files("example.txt");
+        let result = checksum_after_moving_files("example.txt").unwrap();
         assert_eq!(result, 2858);
     }
 
     #[test]
     fn part2() {
-        let result = checksum_after_moving_files("input.txt");
+        let result = checksum_after_moving_files("input.txt").unwrap();
         assert_eq!(result, 6183632723350);
     }
+
+    #[test]
+    fn rejects_bad_disk_maps() {
+        let violation = validate("2333", INPUT_CHECKS).unwrap_err();
+        assert_eq!(violation.assumption, "disk map ends with a file");
+        let violation = validate("23x", INPUT_CHECKS).unwrap_err();
+        assert_eq!(violation.detail, "'x' at offset 2");
+    }
 }